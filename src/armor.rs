@@ -0,0 +1,259 @@
+//! Minimal ASCII armor encoding and decoding (RFC 4880, section 6).
+
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use zeroize::Zeroizing;
+
+const LINE_LENGTH: usize = 64;
+
+/// A decoded armor block. The data may be a secret key, so it is wiped
+/// when the block is dropped.
+pub struct Block {
+    pub label: String,
+    pub headers: Vec<(String, String)>,
+    pub data: Zeroizing<Vec<u8>>,
+}
+
+/// Armors `data` as `-----BEGIN <label>-----` with the given headers and a
+/// CRC24 checksum line.
+pub fn encode(label: &str, headers: &[(String, String)], data: &[u8]) -> String {
+    let mut out = format!("-----BEGIN {}-----\n", label);
+    for (key, value) in headers {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    out.push('\n');
+    let body = STANDARD.encode(data);
+    for line in body.as_bytes().chunks(LINE_LENGTH) {
+        out.push_str(std::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str(&format!("={}\n", STANDARD.encode(&crc24(data).to_be_bytes()[1..])));
+    out.push_str(&format!("-----END {}-----\n", label));
+    out
+}
+
+/// State of the optional CRC24 line of an armor block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Checksum {
+    Valid,
+    Missing,
+    Mismatch { found: String, expected: String },
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Checksum::Valid => write!(f, "valid"),
+            Checksum::Missing => write!(f, "missing (it is optional)"),
+            Checksum::Mismatch { found, expected } => {
+                write!(f, "mismatch: found ={}, expected ={}", found, expected)
+            }
+        }
+    }
+}
+
+/// Decodes every armor block found in `text`, verifying checksums.
+pub fn decode_all(text: &str) -> Result<Vec<Block>, String> {
+    decode_checked(text)?
+        .into_iter()
+        .map(|(block, checksum)| match checksum {
+            Checksum::Mismatch { .. } => Err(format!("Checksum mismatch in {} block", block.label)),
+            _ => Ok(block),
+        })
+        .collect()
+}
+
+/// Decodes every armor block found in `text` and reports the state of each
+/// checksum instead of failing on a mismatch.
+pub fn decode_checked(text: &str) -> Result<Vec<(Block, Checksum)>, String> {
+    let mut blocks = Vec::new();
+    let mut lines = text.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        let Some(label) = begin_label(line) else {
+            continue;
+        };
+        let end = format!("-----END {}-----", label);
+
+        let mut headers = Vec::new();
+        let mut body = Zeroizing::new(String::with_capacity(text.len()));
+        let mut checksum = None;
+        let mut in_headers = true;
+        let mut closed = false;
+        for line in lines.by_ref() {
+            if line == end {
+                closed = true;
+                break;
+            }
+            if in_headers {
+                if line.is_empty() {
+                    in_headers = false;
+                } else if let Some((key, value)) = line.split_once(": ") {
+                    headers.push((key.to_string(), value.to_string()));
+                } else {
+                    in_headers = false;
+                    body.push_str(line);
+                }
+                continue;
+            }
+            match line.strip_prefix('=') {
+                Some(crc) if crc.len() == 4 => checksum = Some(crc.to_string()),
+                _ => body.push_str(line),
+            }
+        }
+        if !closed {
+            return Err(format!("Missing \"{}\" line", end));
+        }
+
+        let mut data = Zeroizing::new(Vec::with_capacity(body.len() / 4 * 3 + 3));
+        STANDARD
+            .decode_vec(body.as_bytes(), &mut data)
+            .map_err(|e| format!("Invalid base64 in {} block: {}", label, e))?;
+        let expected = STANDARD.encode(&crc24(&data).to_be_bytes()[1..]);
+        let checksum = match checksum {
+            None => Checksum::Missing,
+            Some(found) if found == expected => Checksum::Valid,
+            Some(found) => Checksum::Mismatch { found, expected },
+        };
+        blocks.push((
+            Block {
+                label: label.to_string(),
+                headers,
+                data,
+            },
+            checksum,
+        ));
+    }
+    Ok(blocks)
+}
+
+/// Undoes common copy-paste damage to armored text: email quoting (`> `),
+/// indentation, spaces inside base64 lines, header values wrapped onto the
+/// next line and a missing blank line after the headers. Text outside armor
+/// blocks and the text of cleartext signed messages only lose their quote
+/// markers.
+pub fn repair(text: &str) -> String {
+    let mut out = String::new();
+    let mut in_block = false;
+    let mut in_headers = false;
+    let mut cleartext = false;
+    let mut lines = text.lines().map(unquote).peekable();
+    while let Some(raw) = lines.next() {
+        let line = raw.trim();
+        if !in_block {
+            match begin_label(line) {
+                Some(label) => {
+                    in_block = true;
+                    in_headers = true;
+                    cleartext = label == "PGP SIGNED MESSAGE";
+                    out.push_str(line);
+                }
+                None => out.push_str(raw.trim_end()),
+            }
+            out.push('\n');
+            continue;
+        }
+        if cleartext && !in_headers {
+            if begin_label(line).is_some() {
+                // The signature block ends the signed text.
+                cleartext = false;
+                in_headers = true;
+                out.push_str(line);
+            } else {
+                out.push_str(raw.trim_end());
+            }
+            out.push('\n');
+            continue;
+        }
+        if line.starts_with("-----END ") {
+            if in_headers {
+                out.push('\n');
+            }
+            in_block = false;
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        if in_headers {
+            if line.is_empty() {
+                if cleartext {
+                    in_headers = false;
+                    out.push('\n');
+                }
+                // Otherwise repeated blank lines collapse into the separator.
+                continue;
+            }
+            if line.contains(": ") {
+                out.push_str(line);
+                // A header value wrapped onto the next line is not base64.
+                while let Some(next) = lines.next_if(|next| {
+                    let next = next.trim();
+                    !next.is_empty() && !next.starts_with("-----") && !is_base64(next)
+                }) {
+                    let next = next.trim();
+                    out.push(if next.contains(": ") { '\n' } else { ' ' });
+                    out.push_str(next);
+                }
+                out.push('\n');
+                continue;
+            }
+            in_headers = false;
+            out.push('\n');
+        }
+        let line: String = line.split_whitespace().collect();
+        if !line.is_empty() {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Parses armor headers written one `Key: value` per line.
+pub fn parse_headers(text: &str) -> Result<Vec<(String, String)>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once(':') {
+            Some((key, value))
+                if !key.is_empty() && key.chars().all(|c| c.is_ascii_graphic()) =>
+            {
+                Ok((key.to_string(), value.trim().to_string()))
+            }
+            _ => Err(format!("\"{}\" is not a \"Key: value\" header", line)),
+        })
+        .collect()
+}
+
+fn begin_label(line: &str) -> Option<&str> {
+    line.strip_prefix("-----BEGIN ")
+        .and_then(|l| l.strip_suffix("-----"))
+}
+
+/// Strips email quote markers from the start of a line.
+fn unquote(mut line: &str) -> &str {
+    while let Some(rest) = line.trim_start().strip_prefix('>') {
+        line = rest.strip_prefix(' ').unwrap_or(rest);
+    }
+    line
+}
+
+fn is_base64(line: &str) -> bool {
+    line.chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '='))
+}
+
+/// CRC24 as used by OpenPGP ASCII armor.
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xB704CE;
+    for byte in data {
+        crc ^= (*byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864CFB;
+            }
+        }
+    }
+    crc & 0xFFFFFF
+}
@@ -0,0 +1,685 @@
+//! Health check of a key: weak algorithms, SHA-1 self-signatures, expiry,
+//! revoked or expired subkeys, a missing encryption subkey and signing
+//! subkeys without cross-certification. Only self-signatures that verify
+//! against the primary key are taken into account, so a forged revocation
+//! or binding cannot change what the key looks like.
+
+use std::fmt;
+use std::ops::Range;
+
+use pgp::native::{
+    packet::{PublicKey, Signature, UserAttribute, UserId},
+    types::{Tag, Version},
+};
+
+use crate::{fingerprint, packets, paperkey, rfc9580};
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_SECRET_SUBKEY: u8 = 7;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_USER_ATTRIBUTE: u8 = 17;
+
+const SIG_CERTIFICATIONS: [u8; 4] = [0x10, 0x11, 0x12, 0x13];
+const SIG_SUBKEY_BINDING: u8 = 0x18;
+const SIG_PRIMARY_KEY_BINDING: u8 = 0x19;
+const SIG_DIRECT_KEY: u8 = 0x1f;
+const SIG_KEY_REVOCATION: u8 = 0x20;
+const SIG_SUBKEY_REVOCATION: u8 = 0x28;
+
+const FLAG_SIGN: u8 = 0x02;
+const FLAGS_ENCRYPT: u8 = 0x04 | 0x08;
+
+/// Preferred ciphers, hashes and compression, features, and the preferred
+/// AEAD algorithms and ciphersuites.
+const PREFERENCE_SUBPACKETS: [u8; 6] = [11, 21, 22, 30, 34, 39];
+
+/// MD5, SHA-1 and RIPEMD-160.
+const WEAK_HASHES: [u8; 3] = [1, 2, 3];
+/// Expiry further away than five years is reported.
+const MAX_VALIDITY: u32 = 5 * 365 * 86400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+/// What the tool can do about a finding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FixIt {
+    /// Generate a new key to replace this one.
+    GenerateKey,
+}
+
+impl fmt::Display for FixIt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FixIt::GenerateKey => "Generate a new key",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+    pub fix: Option<FixIt>,
+}
+
+impl Finding {
+    fn new(severity: Severity, message: String, fix: Option<FixIt>) -> Self {
+        Finding {
+            severity,
+            message,
+            fix,
+        }
+    }
+}
+
+/// A signature made by the primary key over itself, a user ID or a subkey.
+struct SelfSig {
+    kind: u8,
+    hash: u8,
+    created: u32,
+    /// Seconds after the key creation, 0 for never.
+    key_expiry: Option<u32>,
+    flags: Option<u8>,
+    /// Whether it embeds a primary key binding signature by the subkey.
+    cross_certified: bool,
+    /// Whether it marks its user ID as the primary one.
+    primary_user_id: bool,
+    /// Hashed preference subpackets, see [`PREFERENCE_SUBPACKETS`].
+    preferences: Vec<(u8, Vec<u8>)>,
+}
+
+struct Key {
+    fingerprint: Vec<u8>,
+    /// 4 for RFC 4880 keys, 6 for RFC 9580 ones.
+    version: u8,
+    algorithm: u8,
+    /// Size of the modulus or prime of RSA, DSA and ElGamal keys.
+    bits: Option<u16>,
+    created: u32,
+    sigs: Vec<SelfSig>,
+}
+
+impl Key {
+    fn key_id_bytes(&self) -> &[u8] {
+        paperkey::key_id(&self.fingerprint)
+    }
+
+    fn key_id(&self) -> String {
+        fingerprint::to_hex(self.key_id_bytes())
+    }
+
+    fn newest<'a>(&'a self, kinds: &[u8]) -> Option<&'a SelfSig> {
+        newest(self.sigs.iter(), kinds)
+    }
+
+    fn has(&self, kind: u8) -> bool {
+        self.sigs.iter().any(|sig| sig.kind == kind)
+    }
+
+    /// When the key expires according to `sig`, `None` for never.
+    fn expires(&self, sig: &SelfSig) -> Option<u32> {
+        sig.key_expiry
+            .filter(|&seconds| seconds != 0)
+            .map(|seconds| self.created.saturating_add(seconds))
+    }
+
+    /// Whether the key may encrypt according to its key flags, or its
+    /// algorithm when it has none.
+    fn can_encrypt(&self, sig: &SelfSig) -> bool {
+        match sig.flags {
+            Some(flags) => flags & FLAGS_ENCRYPT != 0,
+            None => matches!(self.algorithm, 1 | 2 | 16 | 18 | 20),
+        }
+    }
+}
+
+fn newest<'a>(sigs: impl Iterator<Item = &'a SelfSig>, kinds: &[u8]) -> Option<&'a SelfSig> {
+    sigs.filter(|sig| kinds.contains(&sig.kind)).max_by_key(|sig| sig.created)
+}
+
+/// Whether a key can be encrypted to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Usability {
+    pub revoked: bool,
+    /// When the key expired, `None` while it is valid.
+    pub expired: Option<u32>,
+    /// Key IDs of the unrevoked and unexpired subkeys whose flags allow
+    /// encryption, followed by the primary key if its flags do.
+    pub encryption_keys: Vec<Vec<u8>>,
+    /// Key IDs of unrevoked subkeys that allow encryption but have expired.
+    pub expired_encryption_keys: Vec<Vec<u8>>,
+}
+
+impl Usability {
+    pub fn can_encrypt(&self) -> bool {
+        !self.revoked && self.expired.is_none() && !self.encryption_keys.is_empty()
+    }
+
+    /// Whether encrypting to the key needs the override for revoked and
+    /// expired keys.
+    pub fn needs_override(&self) -> bool {
+        self.revoked
+            || self.expired.is_some()
+            || self.encryption_keys.is_empty() && !self.expired_encryption_keys.is_empty()
+    }
+}
+
+/// What the signatures following a packet are made over.
+pub(crate) enum Signed<'a> {
+    Key,
+    UserId(&'a [u8]),
+    UserAttribute(&'a [u8]),
+    /// The body of a public or secret subkey packet.
+    Subkey(&'a [u8]),
+}
+
+/// A primary key with its user IDs, subkeys and self-signatures.
+struct Cert {
+    primary: Key,
+    user_ids: Vec<(String, Vec<SelfSig>)>,
+    subkeys: Vec<Key>,
+    /// Self-signatures that did not verify and were ignored.
+    invalid_sigs: usize,
+}
+
+impl Cert {
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut primary: Option<(Key, &[u8])> = None;
+        let mut user_ids: Vec<(String, Vec<SelfSig>)> = Vec::new();
+        let mut subkeys: Vec<Key> = Vec::new();
+        let mut signed = Signed::Key;
+        let mut invalid_sigs = 0;
+        for packet in paperkey::parse_packets(data)? {
+            match packet.tag {
+                TAG_PUBLIC_KEY | TAG_SECRET_KEY if primary.is_none() => {
+                    primary = Some((read_key(packet.body)?, packet.body));
+                }
+                TAG_PUBLIC_KEY | TAG_SECRET_KEY => {
+                    return Err("Only one key can be checked at a time".to_string())
+                }
+                TAG_PUBLIC_SUBKEY | TAG_SECRET_SUBKEY => {
+                    subkeys.push(read_key(packet.body)?);
+                    signed = Signed::Subkey(packet.body);
+                }
+                TAG_USER_ID => {
+                    let user_id = String::from_utf8_lossy(packet.body).into_owned();
+                    user_ids.push((user_id, Vec::new()));
+                    signed = Signed::UserId(packet.body);
+                }
+                TAG_USER_ATTRIBUTE => {
+                    user_ids.push(("photo ID".to_string(), Vec::new()));
+                    signed = Signed::UserAttribute(packet.body);
+                }
+                TAG_SIGNATURE => {
+                    let Some((primary, primary_body)) = &mut primary else {
+                        continue;
+                    };
+                    let Some(sig) = read_self_signature(packet.body, &primary.fingerprint)? else {
+                        continue;
+                    };
+                    if !verify(primary_body, &signed, packet.body) {
+                        invalid_sigs += 1;
+                        continue;
+                    }
+                    if let Some(subkey) = subkeys.last_mut() {
+                        subkey.sigs.push(sig);
+                    } else if let Some((_, sigs)) = user_ids.last_mut() {
+                        sigs.push(sig);
+                    } else {
+                        primary.sigs.push(sig);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(Cert {
+            primary: primary.ok_or("No key found")?.0,
+            user_ids,
+            subkeys,
+            invalid_sigs,
+        })
+    }
+
+    /// The newest self-signature of the primary key, which states its
+    /// current expiry and key flags.
+    fn primary_sig(&self) -> Option<&SelfSig> {
+        let self_sigs = self.user_ids.iter().flat_map(|(_, sigs)| sigs).chain(&self.primary.sigs);
+        newest(self_sigs, &[SIG_CERTIFICATIONS.as_slice(), &[SIG_DIRECT_KEY]].concat())
+    }
+
+    /// The primary user ID with its newest self-signature: the user ID
+    /// marked primary in the newest self-signature, or else the one signed
+    /// last (RFC 4880, section 5.2.3.19).
+    fn primary_user(&self) -> Option<(&str, &SelfSig)> {
+        self.user_ids
+            .iter()
+            .filter_map(|(user_id, sigs)| {
+                Some((user_id.as_str(), newest(sigs.iter(), &SIG_CERTIFICATIONS)?))
+            })
+            .max_by_key(|(_, sig)| (sig.primary_user_id, sig.created))
+    }
+
+    /// The primary user ID, or the key ID when there is none.
+    fn name(&self) -> String {
+        match self.primary_user() {
+            Some((user_id, _)) => user_id.to_string(),
+            None => self.primary.key_id(),
+        }
+    }
+
+    fn usability(&self, now: u32) -> Usability {
+        let primary_sig = self.primary_sig();
+        let mut encryption_keys = Vec::new();
+        let mut expired_encryption_keys = Vec::new();
+        for subkey in &self.subkeys {
+            if subkey.has(SIG_SUBKEY_REVOCATION) {
+                continue;
+            }
+            let Some(binding) = subkey.newest(&[SIG_SUBKEY_BINDING]) else {
+                continue;
+            };
+            if !subkey.can_encrypt(binding) {
+                continue;
+            }
+            if subkey.expires(binding).is_some_and(|expires| expires <= now) {
+                expired_encryption_keys.push(subkey.key_id_bytes().to_vec());
+            } else {
+                encryption_keys.push(subkey.key_id_bytes().to_vec());
+            }
+        }
+        if primary_sig.is_some_and(|sig| self.primary.can_encrypt(sig)) {
+            encryption_keys.push(self.primary.key_id_bytes().to_vec());
+        }
+        Usability {
+            revoked: self.primary.has(SIG_KEY_REVOCATION),
+            expired: primary_sig
+                .and_then(|sig| self.primary.expires(sig))
+                .filter(|&expires| expires <= now),
+            encryption_keys,
+            expired_encryption_keys,
+        }
+    }
+}
+
+/// Checks whether a binary public key can be encrypted to as of `now`, in
+/// seconds since the Unix epoch.
+pub fn usability(data: &[u8], now: u32) -> Result<Usability, String> {
+    Cert::parse(data).map(|cert| cert.usability(now))
+}
+
+/// The primary user ID of a binary public key, or its key ID when it has
+/// none.
+pub(crate) fn name(data: &[u8]) -> Result<String, String> {
+    Cert::parse(data).map(|cert| cert.name())
+}
+
+/// Preference subpackets of a key, from the self-signatures that state
+/// them.
+pub(crate) struct KeyPreferences {
+    /// Primary user ID, or the key ID when the key has none.
+    pub(crate) name: String,
+    /// Whether it is an RFC 9580 (v6) key, which reads SEIPD v2 messages
+    /// whether or not its features say so.
+    pub(crate) rfc9580: bool,
+    /// From the newest self-signature of the primary user ID.
+    pub(crate) user_id: Vec<(u8, Vec<u8>)>,
+    /// From the newest direct-key signature, where RFC 9580 keys keep them.
+    pub(crate) direct_key: Vec<(u8, Vec<u8>)>,
+}
+
+/// Reads the preference subpackets of a binary public key. Only
+/// self-signatures that verify are used.
+pub(crate) fn preferences(data: &[u8]) -> Result<KeyPreferences, String> {
+    let cert = Cert::parse(data)?;
+    let user = cert.primary_user();
+    Ok(KeyPreferences {
+        name: cert.name(),
+        rfc9580: cert.primary.version == 6,
+        user_id: user.map(|(_, sig)| sig.preferences.clone()).unwrap_or_default(),
+        direct_key: cert
+            .primary
+            .newest(&[SIG_DIRECT_KEY])
+            .map(|sig| sig.preferences.clone())
+            .unwrap_or_default(),
+    })
+}
+
+/// Checks a binary public or secret key as of `now`, in seconds since the
+/// Unix epoch. The most severe findings come first.
+pub fn audit(data: &[u8], now: u32) -> Result<Vec<Finding>, String> {
+    let cert = Cert::parse(data)?;
+    let primary = &cert.primary;
+    let primary_sig = cert.primary_sig();
+    let usability = cert.usability(now);
+    let mut findings = Vec::new();
+
+    if usability.revoked {
+        findings.push(Finding::new(
+            Severity::Critical,
+            "The key is revoked".to_string(),
+            Some(FixIt::GenerateKey),
+        ));
+    }
+    check_algorithm(primary, "The primary key", &mut findings);
+    match primary_sig.map(|sig| primary.expires(sig)) {
+        None => {}
+        Some(None) => findings.push(Finding::new(
+            Severity::Warning,
+            "The key never expires, so it stays valid if it is lost or stolen".to_string(),
+            None,
+        )),
+        Some(Some(expires)) if expires <= now => findings.push(Finding::new(
+            Severity::Critical,
+            format!("The key expired on {}", packets::format_time(expires)),
+            Some(FixIt::GenerateKey),
+        )),
+        Some(Some(expires)) if expires - now > MAX_VALIDITY => findings.push(Finding::new(
+            Severity::Info,
+            format!(
+                "The key expires on {}, more than five years from now",
+                packets::format_time(expires)
+            ),
+            None,
+        )),
+        Some(Some(_)) => {}
+    }
+    for (user_id, sigs) in &cert.user_ids {
+        match newest(sigs.iter(), &SIG_CERTIFICATIONS) {
+            Some(sig) if WEAK_HASHES.contains(&sig.hash) => findings.push(Finding::new(
+                Severity::Warning,
+                format!(
+                    "User ID {:?} is self-signed with {}",
+                    user_id,
+                    packets::hash_algorithm(sig.hash)
+                ),
+                None,
+            )),
+            Some(_) => {}
+            None => findings.push(Finding::new(
+                Severity::Warning,
+                format!("User ID {:?} has no self-signature", user_id),
+                None,
+            )),
+        }
+    }
+
+    for subkey in &cert.subkeys {
+        let name = format!("Subkey {}", subkey.key_id());
+        check_algorithm(subkey, &name, &mut findings);
+        if subkey.has(SIG_SUBKEY_REVOCATION) {
+            findings.push(Finding::new(Severity::Info, format!("{} is revoked", name), None));
+            continue;
+        }
+        let Some(binding) = subkey.newest(&[SIG_SUBKEY_BINDING]) else {
+            findings.push(Finding::new(
+                Severity::Warning,
+                format!("{} has no binding signature and cannot be used", name),
+                None,
+            ));
+            continue;
+        };
+        if WEAK_HASHES.contains(&binding.hash) {
+            findings.push(Finding::new(
+                Severity::Warning,
+                format!("{} is bound with {}", name, packets::hash_algorithm(binding.hash)),
+                None,
+            ));
+        }
+        if let Some(expires) = subkey.expires(binding).filter(|&expires| expires <= now) {
+            findings.push(Finding::new(
+                Severity::Info,
+                format!("{} expired on {}", name, packets::format_time(expires)),
+                None,
+            ));
+            continue;
+        }
+        if binding.flags.is_some_and(|flags| flags & FLAG_SIGN != 0) && !binding.cross_certified {
+            findings.push(Finding::new(
+                Severity::Warning,
+                format!(
+                    "{} can sign but is not cross-certified, so its signatures are rejected",
+                    name
+                ),
+                None,
+            ));
+        }
+    }
+    if cert.invalid_sigs > 0 {
+        findings.push(Finding::new(
+            Severity::Warning,
+            format!("{} self-signature(s) do not verify and were ignored", cert.invalid_sigs),
+            None,
+        ));
+    }
+    if usability.encryption_keys.is_empty() {
+        findings.push(Finding::new(
+            Severity::Critical,
+            "No usable encryption subkey, nobody can encrypt to this key".to_string(),
+            Some(FixIt::GenerateKey),
+        ));
+    }
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    Ok(findings)
+}
+
+fn check_algorithm(key: &Key, name: &str, findings: &mut Vec<Finding>) {
+    let bits = key.bits.unwrap_or(0);
+    let finding = match key.algorithm {
+        1..=3 if bits < 2048 => Finding::new(
+            Severity::Critical,
+            format!("{} uses RSA-{}, which is too short to be safe", name, bits),
+            Some(FixIt::GenerateKey),
+        ),
+        17 => Finding::new(
+            Severity::Warning,
+            format!("{} uses DSA-{}, which is deprecated", name, bits),
+            Some(FixIt::GenerateKey),
+        ),
+        16 | 20 => Finding::new(
+            Severity::Warning,
+            format!("{} uses ElGamal-{}, which is deprecated", name, bits),
+            Some(FixIt::GenerateKey),
+        ),
+        _ => return,
+    };
+    findings.push(finding);
+}
+
+fn read_key(body: &[u8]) -> Result<Key, String> {
+    let public_len = paperkey::public_key_len(body)?;
+    let algorithm = body[5];
+    // V6 keys put the length of their key material before it.
+    let material = if body[0] == 6 { 10 } else { 6 };
+    Ok(Key {
+        fingerprint: paperkey::fingerprint(&body[..public_len])?,
+        version: body[0],
+        algorithm,
+        bits: match algorithm {
+            1..=3 | 16 | 17 | 20 => body
+                .get(material..material + 2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]])),
+            _ => None,
+        },
+        created: u32::from_be_bytes([body[1], body[2], body[3], body[4]]),
+        sigs: Vec::new(),
+    })
+}
+
+/// Reads the public part of a key packet with the OpenPGP library, `None`
+/// for algorithms or versions it does not support.
+fn read_public_key(body: &[u8]) -> Option<PublicKey> {
+    let public_len = paperkey::public_key_len(body).ok()?;
+    PublicKey::from_slice(Version::New, &body[..public_len]).ok()
+}
+
+/// Whether signatures by the primary key packet body `primary` can be
+/// checked, which needs an algorithm and version the libraries support.
+pub(crate) fn can_verify(primary: &[u8]) -> bool {
+    if primary.first() == Some(&6) {
+        rfc9580::read_public_key(primary).is_some()
+    } else {
+        read_public_key(primary).is_some()
+    }
+}
+
+/// Whether the signature packet `body` is a valid signature by the primary
+/// key packet body `primary` over what `signed` names. V6 signatures are
+/// checked by [`rfc9580`].
+pub(crate) fn verify(primary: &[u8], signed: &Signed, body: &[u8]) -> bool {
+    if body.first() == Some(&6) {
+        return rfc9580::verify_self_signature(primary, signed, body);
+    }
+    let (Some(primary), Ok(sig)) =
+        (read_public_key(primary), Signature::from_slice(Version::New, body))
+    else {
+        return false;
+    };
+    let primary = &primary;
+    match signed {
+        Signed::Key => sig.verify_key(primary),
+        Signed::UserId(id) => UserId::from_slice(Version::New, id)
+            .and_then(|id| sig.verify_certification(primary, Tag::UserId, &id)),
+        Signed::UserAttribute(attribute) => UserAttribute::from_slice(Version::New, attribute)
+            .and_then(|attribute| {
+                sig.verify_certification(primary, Tag::UserAttribute, &attribute)
+            }),
+        Signed::Subkey(subkey) => match read_public_key(subkey) {
+            Some(subkey) => sig.verify_key_binding(primary, &subkey),
+            None => return false,
+        },
+    }
+    .is_ok()
+}
+
+/// Reads a v4 or v6 signature issued by the key with the fingerprint
+/// `primary`. Signatures by other keys and other versions give `None`.
+fn read_self_signature(body: &[u8], primary: &[u8]) -> Result<Option<SelfSig>, String> {
+    if !matches!(body.first(), Some(4 | 6)) {
+        return Ok(None);
+    }
+    let (hashed, unhashed) = subpacket_areas(body)?;
+    if !issued_by(body, primary) {
+        return Ok(None);
+    }
+
+    let mut sig = SelfSig {
+        kind: body[1],
+        hash: body[3],
+        created: 0,
+        key_expiry: None,
+        flags: None,
+        cross_certified: false,
+        primary_user_id: false,
+        preferences: Vec::new(),
+    };
+    let hashed = subpackets(&body[hashed])?.into_iter().map(|s| (true, s));
+    let unhashed = subpackets(&body[unhashed])?.into_iter().map(|s| (false, s));
+    for (hashed, (kind, data)) in hashed.chain(unhashed) {
+        match kind {
+            2 if hashed && data.len() == 4 => {
+                sig.created = u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+            }
+            9 if hashed && data.len() == 4 => {
+                sig.key_expiry = Some(u32::from_be_bytes([data[0], data[1], data[2], data[3]]))
+            }
+            25 if hashed => sig.primary_user_id = data.first().is_some_and(|&b| b != 0),
+            27 if hashed => sig.flags = data.first().copied(),
+            kind if hashed && PREFERENCE_SUBPACKETS.contains(&kind) => {
+                sig.preferences.push((kind, data.to_vec()))
+            }
+            32 => sig.cross_certified |= data.get(1) == Some(&SIG_PRIMARY_KEY_BINDING),
+            _ => {}
+        }
+    }
+    Ok(Some(sig))
+}
+
+/// Whether the v4 or v6 signature packet `body` names the key with the
+/// fingerprint `primary` as its issuer, by key ID or fingerprint.
+/// Signatures that name no issuer count as issued by it.
+pub(crate) fn issued_by(body: &[u8], primary: &[u8]) -> bool {
+    let Ok((hashed, unhashed)) = subpacket_areas(body) else {
+        return false;
+    };
+    let mut issued = true;
+    for area in [hashed, unhashed] {
+        for (kind, data) in subpackets(&body[area]).unwrap_or_default() {
+            match kind {
+                16 => issued = data == paperkey::key_id(primary),
+                33 if !data.is_empty() => issued = &data[1..] == primary,
+                _ => {}
+            }
+        }
+    }
+    issued
+}
+
+/// Where the hashed and the unhashed subpacket areas of a v4 or v6
+/// signature packet body are. V6 signatures state the area lengths in four
+/// bytes instead of two (RFC 9580, section 5.2.3).
+pub(crate) fn subpacket_areas(body: &[u8]) -> Result<(Range<usize>, Range<usize>), String> {
+    let truncated = || "Signature packet is truncated".to_string();
+    let size = match body.first() {
+        Some(4) => 2,
+        Some(6) => 4,
+        _ => return Err("Only v4 and v6 signatures are supported".to_string()),
+    };
+    let area_len = |pos: usize| -> Result<usize, String> {
+        let b = body.get(pos..pos + size).ok_or_else(truncated)?;
+        Ok(b.iter().fold(0, |len, &byte| len << 8 | byte as usize))
+    };
+    let hashed_start = 4 + size;
+    let hashed_end = hashed_start + area_len(4)?;
+    let unhashed_end = hashed_end + size + area_len(hashed_end)?;
+    if body.len() < unhashed_end {
+        return Err(truncated());
+    }
+    Ok((hashed_start..hashed_end, hashed_end + size..unhashed_end))
+}
+
+/// Splits a signature subpacket area into (type, body) pairs.
+fn subpackets(area: &[u8]) -> Result<Vec<(u8, &[u8])>, String> {
+    let truncated = || "Signature subpacket is truncated".to_string();
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < area.len() {
+        let first = area[pos] as usize;
+        let (len, header) = match first {
+            0..=191 => (first, 1),
+            192..=254 => {
+                let second = *area.get(pos + 1).ok_or_else(truncated)? as usize;
+                (((first - 192) << 8) + second + 192, 2)
+            }
+            _ => {
+                let b = area.get(pos + 1..pos + 5).ok_or_else(truncated)?;
+                (u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize, 5)
+            }
+        };
+        let body = area
+            .get(pos + header..pos + header + len)
+            .filter(|body| !body.is_empty())
+            .ok_or_else(truncated)?;
+        out.push((body[0] & 0x7f, &body[1..]));
+        pos += header + len;
+    }
+    Ok(out)
+}
@@ -0,0 +1,550 @@
+//! Command-line front end for scripting and CI. It goes through the same
+//! core operations as the GUI, so the output is identical.
+//!
+//! Exit codes: 0 success, 1 invalid signature, 2 usage error, 3 I/O error,
+//! 4 any other failure.
+
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use another_pgp_tool::compression::Compression;
+use another_pgp_tool::detect::{self, Content};
+use another_pgp_tool::literal::{DataMode, LiteralInfo, LiteralOptions};
+use another_pgp_tool::{armor, crypto, fingerprint, packets, symmetric, Error, Field, Zeroizing};
+use clap::{Parser, Subcommand};
+
+const PASSPHRASE_ENV: &str = "ANOTHER_PGP_TOOL_PASSPHRASE";
+
+const EXIT_BAD_SIGNATURE: u8 = 1;
+const EXIT_IO: u8 = 3;
+const EXIT_FAILURE: u8 = 4;
+
+#[derive(Parser)]
+#[command(name = "another_pgp_tool_cli", version, about = "Yet Another PGP Tool")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new key pair
+    Generate {
+        /// Where to write the private key (default: stdout)
+        #[arg(long)]
+        secret_out: Option<PathBuf>,
+        /// Where to write the public key (default: stdout)
+        #[arg(long)]
+        public_out: Option<PathBuf>,
+        /// Key format: rfc4880 (v4) or rfc9580 (v6)
+        #[arg(long, default_value = "rfc4880")]
+        profile: crypto::KeyProfile,
+    },
+    /// Encrypt a message to a public key or with a passphrase
+    Encrypt {
+        /// Recipient's public key file, repeat for several recipients
+        #[arg(short, long, required_unless_present = "symmetric")]
+        recipient: Vec<PathBuf>,
+        /// Encrypt with a passphrase only (read from the passphrase file or
+        /// the ANOTHER_PGP_TOOL_PASSPHRASE environment variable)
+        #[arg(short, long)]
+        symmetric: bool,
+        /// Passphrase hashing with --symmetric: iterated, or argon2 for a
+        /// SEIPD v2 message only RFC 9580 implementations read
+        #[arg(long, default_value = "iterated")]
+        s2k: symmetric::S2kKind,
+        /// Compression: none, zip, zlib or bzip2 (default: the one all
+        /// recipients prefer, zlib with a passphrase)
+        #[arg(long)]
+        compression: Option<Compression>,
+        /// Encrypt even to revoked or expired recipient keys
+        #[arg(long)]
+        allow_unusable: bool,
+        #[command(flatten)]
+        literal: LiteralArgs,
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Decrypt a message with a private key or a passphrase
+    Decrypt {
+        /// Your private key file
+        #[arg(short, long, required_unless_present = "symmetric")]
+        key: Option<PathBuf>,
+        /// Decrypt a passphrase-only message
+        #[arg(short, long)]
+        symmetric: bool,
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Create a cleartext signed message, or an inline signed one
+    Sign {
+        /// Your private key file
+        #[arg(short, long)]
+        key: PathBuf,
+        /// Make an inline signed PGP MESSAGE, like gpg --sign, which keeps
+        /// binary data intact
+        #[arg(long)]
+        inline: bool,
+        /// Compression with --inline: none, zip, zlib or bzip2 (default:
+        /// zlib)
+        #[arg(long, requires = "inline")]
+        compression: Option<Compression>,
+        #[command(flatten)]
+        literal: LiteralArgs,
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Verify a cleartext or inline signed message and print the signed
+    /// data
+    Verify {
+        /// Signer's public key file
+        #[arg(short, long)]
+        key: PathBuf,
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Print the fingerprint and user IDs of a public key
+    Inspect {
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Check a public or private key for weak algorithms, expiry and other
+    /// problems
+    Audit {
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Merge copies of the same public key into one key with every user ID,
+    /// subkey and signature
+    MergeKeys {
+        /// Armored public key files to merge
+        #[arg(required = true)]
+        keys: Vec<PathBuf>,
+        /// Where to write the merged key (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List the packets of armored or binary OpenPGP data without showing
+    /// secret material
+    ListPackets {
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Armor binary OpenPGP data
+    Enarmor {
+        /// Armor type such as "PGP MESSAGE" (default: detected from the data)
+        #[arg(long)]
+        label: Option<String>,
+        /// Armor header line such as "Comment: for Bob", may be repeated
+        #[arg(long = "header")]
+        headers: Vec<String>,
+        #[command(flatten)]
+        io: IoArgs,
+    },
+    /// Decode armored OpenPGP data to binary, repairing copy-paste damage,
+    /// and report its headers and checksum on stderr
+    Dearmor {
+        #[command(flatten)]
+        io: IoArgs,
+    },
+}
+
+/// Metadata stored with encrypted or inline signed content.
+#[derive(clap::Args)]
+struct LiteralArgs {
+    /// File name the recipient sees (default: none)
+    #[arg(long, default_value = "")]
+    file_name: String,
+    /// Date the recipient sees, as YYYY-MM-DD[ HH:MM[:SS]] in UTC or "none"
+    /// (default: now)
+    #[arg(long, value_parser = parse_date)]
+    date: Option<u32>,
+    /// Data format: binary, text or utf8
+    #[arg(long, default_value = "binary")]
+    mode: DataMode,
+}
+
+impl LiteralArgs {
+    fn options(self) -> LiteralOptions {
+        LiteralOptions {
+            file_name: self.file_name,
+            date: self.date,
+            mode: self.mode,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct IoArgs {
+    /// Input file (default: stdin)
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+    /// Output file (default: stdout)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// File holding the passphrase (default: ANOTHER_PGP_TOOL_PASSPHRASE or empty)
+    #[arg(long)]
+    passphrase_file: Option<PathBuf>,
+}
+
+enum CliError {
+    Io(String),
+    Pgp(Error),
+}
+
+impl From<Error> for CliError {
+    fn from(e: Error) -> Self {
+        CliError::Pgp(e)
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Io(e)) => {
+            eprintln!("{}", e);
+            ExitCode::from(EXIT_IO)
+        }
+        Err(CliError::Pgp(e)) => {
+            eprintln!("{}\n{}", e, e.suggestion());
+            match e {
+                Error::BadSignature(_) => ExitCode::from(EXIT_BAD_SIGNATURE),
+                _ => ExitCode::from(EXIT_FAILURE),
+            }
+        }
+    }
+}
+
+async fn run(command: Command) -> Result<(), CliError> {
+    match command {
+        Command::Generate {
+            secret_out,
+            public_out,
+            profile,
+        } => {
+            let keys = crypto::generate_keypair_with_profile(profile).await?;
+            if secret_out.is_none() && public_out.is_none() {
+                return write_output(
+                    &None,
+                    &format!("{}\n{}", keys.secret_key.as_str(), keys.public_key),
+                );
+            }
+            write_secret_output(&secret_out, &keys.secret_key)?;
+            write_output(&public_out, &keys.public_key)
+        }
+        Command::Encrypt {
+            recipient,
+            symmetric,
+            compression,
+            allow_unusable,
+            literal,
+            io,
+        } => {
+            let plaintext = Zeroizing::new(read_input_bytes(&io.input)?);
+            let literal = literal.options();
+            let encrypted = if !symmetric {
+                let mut public_keys = String::new();
+                for path in &recipient {
+                    public_keys.push_str(&read_file(path)?);
+                    public_keys.push('\n');
+                }
+                let negotiated = crypto::negotiate_algorithms(&public_keys).await?;
+                eprintln!("Algorithms: {}", negotiated.summary());
+                if let Some(mode) = negotiated.aead {
+                    eprintln!("All recipients accept AEAD {}, SEIPD v2 is written", mode);
+                }
+                for warning in &negotiated.warnings {
+                    eprintln!("Warning: {}", warning);
+                }
+                crypto::encrypt_bytes(
+                    &public_keys,
+                    &plaintext,
+                    &literal,
+                    compression,
+                    allow_unusable,
+                )
+                .await?
+            } else {
+                crypto::encrypt_bytes_with_passphrase(
+                    &read_passphrase(&io.passphrase_file)?,
+                    &plaintext,
+                    symmetric::S2kOptions {
+                        kind: s2k,
+                        ..Default::default()
+                    },
+                    &literal,
+                    compression.unwrap_or(Compression::Zlib),
+                )?
+            };
+            write_output(&io.output, &encrypted)
+        }
+        Command::Decrypt {
+            key,
+            symmetric,
+            io,
+        } => {
+            let encrypted = armored_message(read_input_bytes(&io.input)?)?;
+            let passphrase = read_passphrase(&io.passphrase_file)?;
+            let (literal, data) = match key {
+                Some(key) if !symmetric => {
+                    crypto::decrypt_bytes(&read_secret_file(&key)?, &passphrase, &encrypted).await?
+                }
+                _ => crypto::decrypt_bytes_with_passphrase(&passphrase, &encrypted)?,
+            };
+            report_literal(&literal);
+            write_output_bytes(&io.output, &data, true)
+        }
+        Command::Sign {
+            key,
+            inline,
+            compression,
+            literal,
+            io,
+        } => {
+            let message = Zeroizing::new(read_input_bytes(&io.input)?);
+            let passphrase = read_passphrase(&io.passphrase_file)?;
+            let secret_key = read_secret_file(&key)?;
+            let signed = if inline {
+                let literal = literal.options();
+                let compression = compression.unwrap_or(Compression::Zlib);
+                crypto::sign_inline(&secret_key, &passphrase, &message, &literal, compression)
+                    .await?
+            } else {
+                let message = std::str::from_utf8(&message).map_err(|e| {
+                    Error::InvalidUtf8(format!("cleartext signatures need text: {}", e))
+                })?;
+                crypto::sign_cleartext(&secret_key, &passphrase, message).await?
+            };
+            write_output(&io.output, &signed)
+        }
+        Command::Verify { key, io } => {
+            let signed = armored_message(read_input_bytes(&io.input)?)?;
+            let public_key = read_file(&key)?;
+            if detect::armored_content(&signed) == Some(Content::Message) {
+                let (literal, data) = crypto::verify_inline(&public_key, &signed).await?;
+                report_literal(&literal);
+                return write_output_bytes(&io.output, &data, false);
+            }
+            let message = crypto::verify_cleartext(&public_key, &signed).await?;
+            write_output(&io.output, &message)
+        }
+        Command::Inspect { io } => {
+            let info = crypto::inspect_public_key(&read_input(&io.input)?).await?;
+            let mut report = format!(
+                "Fingerprint: {}\nWords: {}\n",
+                fingerprint::to_grouped_hex(&info.fingerprint),
+                fingerprint::to_words(&info.fingerprint).join(" ")
+            );
+            for user_id in &info.user_ids {
+                report.push_str(&format!("User ID: {}\n", user_id));
+            }
+            if let Some(url) = &info.wkd_url {
+                report.push_str(&format!("WKD URL: {}\n", url));
+            }
+            write_output(&io.output, report.trim_end())
+        }
+        Command::Audit { io } => {
+            let key = Zeroizing::new(read_input(&io.input)?);
+            let findings = crypto::audit_key(&key)?;
+            let mut report = String::new();
+            for finding in &findings {
+                report.push_str(&format!("{}: {}", finding.severity, finding.message));
+                if let Some(fix) = finding.fix {
+                    report.push_str(&format!(" (fix: {})", fix));
+                }
+                report.push('\n');
+            }
+            if findings.is_empty() {
+                report.push_str("No problems found");
+            }
+            write_output(&io.output, report.trim_end())
+        }
+        Command::MergeKeys { keys, output } => {
+            let mut public_keys = String::new();
+            for path in &keys {
+                public_keys.push_str(&read_file(path)?);
+                public_keys.push('\n');
+            }
+            let merged = crypto::merge_keys(&public_keys).await?;
+            eprintln!("{}", merged.report.to_text());
+            write_output(&output, merged.public_key.trim_end())
+        }
+        Command::ListPackets { io } => {
+            let packets = crypto::list_packets(&read_input_bytes(&io.input)?)?;
+            write_output(&io.output, packets::to_text(&packets).trim_end())
+        }
+        Command::Enarmor {
+            label,
+            headers,
+            io,
+        } => {
+            let data = read_input_bytes(&io.input)?;
+            let armored = crypto::enarmor(&data, label.as_deref(), &headers.join("\n"))?;
+            write_output(&io.output, armored.trim_end())
+        }
+        Command::Dearmor { io } => {
+            let input = read_input(&io.input)?;
+            let dearmored = crypto::dearmor(&input)?;
+            eprintln!("Type: {}", dearmored.label);
+            for (key, value) in &dearmored.headers {
+                eprintln!("Header: {}: {}", key, value);
+            }
+            eprintln!("Checksum: {}", dearmored.checksum);
+            if dearmored.repaired {
+                eprintln!("Repaired copy-paste damage in the input");
+            }
+            if let armor::Checksum::Mismatch { .. } = dearmored.checksum {
+                return Err(Error::MalformedArmor(
+                    Field::PacketData,
+                    format!("checksum {}", dearmored.checksum),
+                )
+                .into());
+            }
+            // The data may be a private key.
+            write_output_bytes(&io.output, &dearmored.data, true)
+        }
+    }
+}
+
+fn parse_date(text: &str) -> Result<u32, String> {
+    match text {
+        "none" => Ok(0),
+        _ => packets::parse_time(text).ok_or_else(|| format!("invalid date \"{}\"", text)),
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, CliError> {
+    fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("Error reading {}: {}", path.display(), e)))
+}
+
+/// Reads a file holding a secret key or passphrase into a buffer that is
+/// wiped when dropped.
+fn read_secret_file(path: &Path) -> Result<Zeroizing<String>, CliError> {
+    read_file(path).map(Zeroizing::new)
+}
+
+fn read_input(path: &Option<PathBuf>) -> Result<String, CliError> {
+    match path {
+        Some(path) => read_file(path),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| CliError::Io(format!("Error reading stdin: {}", e)))?;
+            Ok(input)
+        }
+    }
+}
+
+/// Reads input that may be binary, such as a `.gpg` file.
+fn read_input_bytes(path: &Option<PathBuf>) -> Result<Vec<u8>, CliError> {
+    match path {
+        Some(path) => fs::read(path)
+            .map_err(|e| CliError::Io(format!("Error reading {}: {}", path.display(), e))),
+        None => {
+            let mut input = Vec::new();
+            io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|e| CliError::Io(format!("Error reading stdin: {}", e)))?;
+            Ok(input)
+        }
+    }
+}
+
+fn read_passphrase(path: &Option<PathBuf>) -> Result<Zeroizing<String>, CliError> {
+    match path {
+        Some(path) => {
+            let mut passphrase = read_secret_file(path)?;
+            let len = passphrase.trim_end_matches(['\r', '\n']).len();
+            passphrase.truncate(len);
+            Ok(passphrase)
+        }
+        None => Ok(Zeroizing::new(env::var(PASSPHRASE_ENV).unwrap_or_default())),
+    }
+}
+
+/// Encrypted input as an armored message; binary `.gpg` files are armored
+/// first.
+/// Reports the file name and date stored with decrypted or verified data.
+fn report_literal(literal: &LiteralInfo) {
+    if !literal.file_name.is_empty() {
+        eprintln!("File name: {}", literal.file_name);
+    }
+    if literal.date != 0 {
+        eprintln!("Date: {}", packets::format_time(literal.date));
+    }
+}
+
+fn armored_message(data: Vec<u8>) -> Result<String, CliError> {
+    match String::from_utf8(data) {
+        Ok(text) => Ok(text),
+        Err(e) => Ok(crypto::enarmor(e.as_bytes(), Some("PGP MESSAGE"), "")?),
+    }
+}
+
+/// Writes binary output as is, without a trailing newline. Output that may
+/// be secret, such as private keys and decrypted data, goes to files only
+/// the owner can read.
+fn write_output_bytes(
+    path: &Option<PathBuf>,
+    content: &[u8],
+    secret: bool,
+) -> Result<(), CliError> {
+    match path {
+        Some(path) => create_file(path, secret)
+            .and_then(|mut file| file.write_all(content))
+            .map_err(|e| CliError::Io(format!("Error writing {}: {}", path.display(), e))),
+        None => {
+            let mut stdout = io::stdout();
+            stdout
+                .write_all(content)
+                .and_then(|_| stdout.flush())
+                .map_err(|e| CliError::Io(format!("Error writing stdout: {}", e)))
+        }
+    }
+}
+
+/// Writes text output ending in one newline, to a file or stdout alike.
+fn write_output(path: &Option<PathBuf>, content: &str) -> Result<(), CliError> {
+    write_text(path, content, false)
+}
+
+/// Like [`write_output`], for private keys.
+fn write_secret_output(path: &Option<PathBuf>, content: &str) -> Result<(), CliError> {
+    write_text(path, content, true)
+}
+
+fn write_text(path: &Option<PathBuf>, content: &str, secret: bool) -> Result<(), CliError> {
+    let mut text = Zeroizing::new(content.to_string());
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    write_output_bytes(path, text.as_bytes(), secret)
+}
+
+#[cfg(unix)]
+fn create_file(path: &Path, secret: bool) -> io::Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    if !secret {
+        return options.open(path);
+    }
+    let file = options.mode(0o600).open(path)?;
+    // The mode only applies to new files, so existing ones are narrowed too.
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create_file(path: &Path, _secret: bool) -> io::Result<fs::File> {
+    fs::File::create(path)
+}
@@ -0,0 +1,93 @@
+//! Clipboard access for the copy and paste buttons. The desktop build talks
+//! to the system clipboard directly, other platforms go through the
+//! browser's clipboard API.
+
+use std::time::Duration;
+
+#[cfg(feature = "desktop")]
+use another_pgp_tool::Zeroizing;
+#[cfg(not(feature = "desktop"))]
+use dioxus::prelude::document;
+
+#[cfg(feature = "desktop")]
+thread_local! {
+    // Some platforms drop the clipboard contents together with the last
+    // handle, so one is kept for the lifetime of the app.
+    static CLIPBOARD: std::cell::RefCell<Option<arboard::Clipboard>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(feature = "desktop")]
+fn with_clipboard<T>(
+    f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+) -> Result<T, String> {
+    CLIPBOARD.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        if cell.is_none() {
+            *cell = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        f(cell.as_mut().expect("clipboard initialized above")).map_err(|e| e.to_string())
+    })
+}
+
+/// Puts `text` on the clipboard. With `clear_after` set, the clipboard is
+/// emptied after that time unless something else was copied meanwhile.
+#[cfg(feature = "desktop")]
+pub async fn copy(text: &str, clear_after: Option<Duration>) -> Result<(), String> {
+    with_clipboard(|clipboard| clipboard.set_text(text))?;
+    if let Some(delay) = clear_after {
+        let copied = Zeroizing::new(text.to_string());
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let Ok(mut clipboard) = arboard::Clipboard::new() else {
+                return;
+            };
+            let unchanged = clipboard
+                .get_text()
+                .map(Zeroizing::new)
+                .is_ok_and(|current| current == copied);
+            if unchanged {
+                let _ = clipboard.clear();
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+pub async fn paste() -> Result<String, String> {
+    with_clipboard(|clipboard| clipboard.get_text())
+}
+
+/// Puts `text` on the clipboard. With `clear_after` set, the clipboard is
+/// emptied after that time unless something else was copied meanwhile.
+/// Browsers that refuse to let the page read the clipboard get it emptied
+/// unconditionally.
+#[cfg(not(feature = "desktop"))]
+pub async fn copy(text: &str, clear_after: Option<Duration>) -> Result<(), String> {
+    let eval = document::eval(
+        r#"const [text, clearAfter] = await dioxus.recv();
+        await navigator.clipboard.writeText(text);
+        if (clearAfter > 0) {
+            setTimeout(async () => {
+                try {
+                    if (await navigator.clipboard.readText() !== text) {
+                        return;
+                    }
+                } catch (e) {}
+                await navigator.clipboard.writeText("");
+            }, clearAfter);
+        }"#,
+    );
+    let clear_after_ms = clear_after.map_or(0, |delay| delay.as_millis() as u64);
+    eval.send((text, clear_after_ms)).map_err(|e| e.to_string())?;
+    eval.await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "desktop"))]
+pub async fn paste() -> Result<String, String> {
+    document::eval("return await navigator.clipboard.readText();")
+        .join::<String>()
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,166 @@
+//! Compression of message contents before encryption or inline signing (RFC
+//! 4880, section 5.6) and decompression after decrypting or verifying. BZip2
+//! is handled here because the bundled OpenPGP library only implements ZIP
+//! and ZLIB.
+
+use std::{
+    fmt,
+    io::{Read, Write},
+    str::FromStr,
+};
+
+use pgp::native::{
+    packet::CompressedData, ser::Serialize, types::CompressionAlgorithm, Deserializable, Message,
+};
+use zeroize::Zeroizing;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    /// No compressed data packet at all, for data that is already
+    /// compressed or where compression is not allowed.
+    None,
+    Zip,
+    Zlib,
+    Bzip2,
+}
+
+impl Compression {
+    pub const ALL: [Compression; 4] = [
+        Compression::None,
+        Compression::Zip,
+        Compression::Zlib,
+        Compression::Bzip2,
+    ];
+
+    /// Algorithm ID from the OpenPGP registry.
+    pub fn id(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zip => 1,
+            Compression::Zlib => 2,
+            Compression::Bzip2 => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Compression::ALL.into_iter().find(|c| c.id() == id)
+    }
+
+    /// Short name, as accepted by [`Compression::from_str`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Zip => "zip",
+            Compression::Zlib => "zlib",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Compression::ALL
+            .into_iter()
+            .find(|c| c.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown compression \"{}\", use none, zip, zlib or bzip2", s))
+    }
+}
+
+/// Wraps `msg` in a compressed data packet.
+pub(crate) fn compress(msg: Message, compression: Compression) -> Result<Message, String> {
+    if compression == Compression::None {
+        return Ok(msg);
+    }
+    let plain = Zeroizing::new(msg.to_bytes().map_err(|e| e.to_string())?);
+    compressed_data(&plain, compression).map(Message::Compressed)
+}
+
+/// Like [`compress`], for a binary message such as one with a v6 signature,
+/// which the bundled OpenPGP library cannot read.
+pub(crate) fn compress_bytes(plain: &[u8], compression: Compression) -> Result<Vec<u8>, String> {
+    if compression == Compression::None {
+        return Ok(plain.to_vec());
+    }
+    compressed_data(plain, compression)
+        .and_then(|data| Message::Compressed(data).to_bytes().map_err(|e| e.to_string()))
+}
+
+fn compressed_data(plain: &[u8], compression: Compression) -> Result<CompressedData, String> {
+    let (algorithm, compressed) = match compression {
+        Compression::Zip => {
+            let mut encoder =
+                flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            let compressed = encoder.write_all(plain).and_then(|_| encoder.finish());
+            (CompressionAlgorithm::ZIP, compressed)
+        }
+        Compression::Bzip2 => {
+            let mut encoder =
+                bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+            let compressed = encoder.write_all(plain).and_then(|_| encoder.finish());
+            (CompressionAlgorithm::BZip2, compressed)
+        }
+        Compression::None | Compression::Zlib => {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            let compressed = encoder.write_all(plain).and_then(|_| encoder.finish());
+            (CompressionAlgorithm::ZLIB, compressed)
+        }
+    };
+    let compressed = compressed.map_err(|e| format!("compression failed: {}", e))?;
+    Ok(CompressedData::from_compressed(algorithm, compressed))
+}
+
+/// Unpacks a compressed data packet, leaving other messages as they are.
+pub(crate) fn decompress(msg: Message) -> Result<Message, String> {
+    let Message::Compressed(compressed) = &msg else {
+        return Ok(msg);
+    };
+    let data = decompressed_data(compressed)?;
+    Message::from_bytes(&data[..]).map_err(|e| e.to_string())
+}
+
+/// Like [`decompress`], for a binary message whose contents the bundled
+/// OpenPGP library may not read, such as v6 signatures.
+pub(crate) fn decompress_bytes(data: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    match Message::from_bytes(data) {
+        Ok(Message::Compressed(compressed)) => decompressed_data(&compressed),
+        _ => Ok(Zeroizing::new(data.to_vec())),
+    }
+}
+
+fn decompressed_data(compressed: &CompressedData) -> Result<Zeroizing<Vec<u8>>, String> {
+    let body = Zeroizing::new(compressed.to_bytes().map_err(|e| e.to_string())?);
+    let (&algorithm, data) = body.split_first().ok_or("Compressed data packet is empty")?;
+    inflate(algorithm, data, u64::MAX)?
+        .ok_or_else(|| format!("Unsupported compression algorithm {}", algorithm))
+}
+
+/// Decompresses the body of a compressed data packet, reading at most
+/// `limit` bytes. Returns `None` for an unknown algorithm.
+pub(crate) fn inflate(
+    algorithm: u8,
+    data: &[u8],
+    limit: u64,
+) -> Result<Option<Zeroizing<Vec<u8>>>, String> {
+    let reader: Box<dyn Read + '_> = match Compression::from_id(algorithm) {
+        Some(Compression::None) => Box::new(data),
+        Some(Compression::Zip) => Box::new(flate2::read::DeflateDecoder::new(data)),
+        Some(Compression::Zlib) => Box::new(flate2::read::ZlibDecoder::new(data)),
+        Some(Compression::Bzip2) => Box::new(bzip2::read::BzDecoder::new(data)),
+        None => return Ok(None),
+    };
+    let mut out = Zeroizing::new(Vec::new());
+    reader
+        .take(limit)
+        .read_to_end(&mut out)
+        .map_err(|e| format!("decompression failed: {}", e))?;
+    Ok(Some(out))
+}
@@ -0,0 +1,84 @@
+//! Recognizes which kind of OpenPGP data a file or a piece of text holds,
+//! from its armor header line or, for binary data, its first packet tag.
+
+use crate::armor;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Content {
+    PublicKey,
+    SecretKey,
+    Message,
+    SignedMessage,
+    /// A detached signature.
+    Signature,
+}
+
+impl Content {
+    /// Armor label used for this kind of data.
+    pub fn label(self) -> &'static str {
+        match self {
+            Content::PublicKey => "PGP PUBLIC KEY BLOCK",
+            Content::SecretKey => "PGP PRIVATE KEY BLOCK",
+            Content::Message => "PGP MESSAGE",
+            Content::SignedMessage => "PGP SIGNED MESSAGE",
+            Content::Signature => "PGP SIGNATURE",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [
+            Content::PublicKey,
+            Content::SecretKey,
+            Content::Message,
+            Content::SignedMessage,
+            Content::Signature,
+        ]
+        .into_iter()
+        .find(|content| content.label() == label)
+    }
+}
+
+/// Detects the content of `data` and returns it armored. Binary data is
+/// armored here, so callers only ever deal with text. Returns `None` for
+/// anything that is not OpenPGP data.
+pub fn detect(data: &[u8]) -> Option<(Content, String)> {
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Some(content) = armored_content(text) {
+            return Some((content, text.trim().to_string()));
+        }
+    }
+    let content = binary_content(data)?;
+    Some((content, armor::encode(content.label(), &[], data)))
+}
+
+/// Kind of the first armor block in `text`.
+pub fn armored_content(text: &str) -> Option<Content> {
+    text.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("-----BEGIN ")
+            .and_then(|l| l.strip_suffix("-----"))
+            .and_then(Content::from_label)
+    })
+}
+
+/// Kind of binary OpenPGP data, judged by the tag of its first packet.
+pub(crate) fn binary_content(data: &[u8]) -> Option<Content> {
+    let first = *data.first()?;
+    if first & 0x80 == 0 {
+        return None;
+    }
+    let tag = if first & 0x40 != 0 {
+        first & 0x3f
+    } else {
+        (first >> 2) & 0x0f
+    };
+    match tag {
+        // Session keys, one-pass signatures, compressed, literal and
+        // encrypted data all start a message.
+        1 | 3 | 4 | 8 | 9 | 11 | 18 => Some(Content::Message),
+        2 => Some(Content::Signature),
+        5 => Some(Content::SecretKey),
+        6 => Some(Content::PublicKey),
+        _ => None,
+    }
+}
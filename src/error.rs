@@ -0,0 +1,230 @@
+use std::fmt;
+
+use pgp::native::errors::Error as PgpError;
+use rpgp::errors::Error as Rfc9580Error;
+
+/// Defines a function that classifies the errors of one version of the
+/// OpenPGP library. Both versions name their error variants alike.
+macro_rules! classify_library_error {
+    ($name:ident, $error:ident) => {
+        fn $name(field: Field, error: &$error, fallback: fn(String) -> Error) -> Error {
+            let detail = error.to_string();
+            match error {
+                $error::InvalidPacketContent(inner) => match Error::$name(field, inner, fallback) {
+                    unsupported @ Error::UnsupportedAlgorithm(..) => unsupported,
+                    _ => Error::MalformedArmor(field, detail),
+                },
+                $error::ParsingError(_)
+                | $error::PacketError(_)
+                | $error::PacketIncomplete
+                | $error::Incomplete(_)
+                | $error::InvalidInput
+                | $error::InvalidArmorWrappers
+                | $error::InvalidChecksum
+                | $error::Base64DecodeError(_)
+                | $error::NoMatchingPacket
+                | $error::TooManyPackets
+                | $error::MissingPackets => Error::MalformedArmor(field, detail),
+                $error::Unimplemented(_) | $error::Unsupported(_) => {
+                    Error::UnsupportedAlgorithm(field, detail)
+                }
+                $error::MissingKey => Error::NoMatchingKey,
+                // Plain messages carry no type, so their text is all there is.
+                $error::Message(message) => {
+                    let lower = message.to_lowercase();
+                    if lower.contains("expired") {
+                        Error::ExpiredKey(field)
+                    } else if lower.contains("revoked") {
+                        Error::RevokedKey(field)
+                    } else if lower.contains("password") || lower.contains("passphrase") {
+                        Error::WrongPassphrase
+                    } else {
+                        fallback(detail)
+                    }
+                }
+                _ => fallback(detail),
+            }
+        }
+    };
+}
+
+/// Input that an error relates to, so front ends can show the hint next to
+/// the offending field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    PublicKey,
+    SecretKey,
+    Passphrase,
+    Message,
+    EncryptedMessage,
+    SignedMessage,
+    PaperBackup,
+    Shares,
+    PacketData,
+    Date,
+}
+
+/// Errors returned by the operations in [`crate::crypto`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    MissingInput(Field),
+    MalformedArmor(Field, String),
+    WrongKeyType { field: Field, found: &'static str },
+    WrongPassphrase,
+    NoMatchingKey,
+    ExpiredKey(Field),
+    RevokedKey(Field),
+    UnsupportedAlgorithm(Field, String),
+    InvalidSignedMessage(&'static str),
+    BadSignature(String),
+    InvalidUtf8(String),
+    InvalidDate(String),
+    GenerateKeys(String),
+    Encrypt(String),
+    Decrypt(String),
+    Sign(String),
+    PaperBackup(String),
+    SecretSharing(String),
+    Packets(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Classifies an error reported by either version of the OpenPGP library
+    /// while handling `field`, going by the library's error variant found in
+    /// the source chain of `error`. Errors of no known kind become
+    /// `fallback`.
+    pub(crate) fn classify(
+        field: Field,
+        error: &(dyn std::error::Error + 'static),
+        fallback: fn(String) -> Error,
+    ) -> Error {
+        let mut source = Some(error);
+        while let Some(e) = source {
+            if let Some(pgp) = e.downcast_ref::<PgpError>() {
+                return Error::classify_pgp(field, pgp, fallback);
+            }
+            if let Some(pgp) = e.downcast_ref::<Rfc9580Error>() {
+                return Error::classify_rfc9580(field, pgp, fallback);
+            }
+            source = e.source();
+        }
+        fallback(error.to_string())
+    }
+
+    classify_library_error!(classify_pgp, PgpError);
+    classify_library_error!(classify_rfc9580, Rfc9580Error);
+
+    /// The input the user should look at to fix this error.
+    pub fn field(&self) -> Option<Field> {
+        match self {
+            Error::MissingInput(field)
+            | Error::MalformedArmor(field, _)
+            | Error::WrongKeyType { field, .. }
+            | Error::ExpiredKey(field)
+            | Error::RevokedKey(field)
+            | Error::UnsupportedAlgorithm(field, _) => Some(*field),
+            Error::WrongPassphrase => Some(Field::Passphrase),
+            Error::NoMatchingKey => Some(Field::SecretKey),
+            Error::InvalidSignedMessage(_) | Error::BadSignature(_) => Some(Field::SignedMessage),
+            Error::PaperBackup(_) => Some(Field::PaperBackup),
+            Error::SecretSharing(_) => Some(Field::Shares),
+            Error::Packets(_) => Some(Field::PacketData),
+            Error::InvalidDate(_) => Some(Field::Date),
+            Error::InvalidUtf8(_)
+            | Error::GenerateKeys(_)
+            | Error::Encrypt(_)
+            | Error::Decrypt(_)
+            | Error::Sign(_) => None,
+        }
+    }
+
+    /// What the user can do about this error.
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            Error::MissingInput(_) => "Fill in the highlighted field and try again.",
+            Error::MalformedArmor(..) => {
+                "Copy the whole block again, including the -----BEGIN and -----END lines."
+            }
+            Error::WrongKeyType { field: Field::PublicKey, .. } => {
+                "Paste the public key here. Never share your private key."
+            }
+            Error::WrongKeyType { .. } => "Paste your private key here, not a public key.",
+            Error::WrongPassphrase => "Check the passphrase for typos and keyboard layout.",
+            Error::NoMatchingKey => {
+                "The message was encrypted for a different key. Ask the sender which key they used."
+            }
+            Error::ExpiredKey(_) => "Ask the key owner for a key with an extended expiry date.",
+            Error::RevokedKey(_) => "The owner revoked this key. Ask them for their new key.",
+            Error::UnsupportedAlgorithm(..) => {
+                "Ask the key owner for a key using RSA, Ed25519 or Curve25519."
+            }
+            Error::InvalidSignedMessage(_) => {
+                "Paste the entire signed message including the signature block."
+            }
+            Error::BadSignature(_) => {
+                "The message was altered or signed by another key. Do not trust its content."
+            }
+            Error::InvalidUtf8(_) => "The content is binary and cannot be shown as text.",
+            Error::InvalidDate(_) => "Enter the date as YYYY-MM-DD HH:MM in UTC.",
+            Error::PaperBackup(_) => "Compare the backup with the printout line by line.",
+            Error::SecretSharing(_) => "Paste at least the required number of shares.",
+            Error::Packets(_) => {
+                "The data is damaged or not OpenPGP. The listing stops at the offset shown."
+            }
+            Error::GenerateKeys(_) | Error::Encrypt(_) | Error::Decrypt(_) | Error::Sign(_) => {
+                "Check the inputs and try again."
+            }
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Field::PublicKey => "public key",
+            Field::SecretKey => "private key",
+            Field::Passphrase => "passphrase",
+            Field::Message => "message",
+            Field::EncryptedMessage => "encrypted message",
+            Field::SignedMessage => "signed message",
+            Field::PaperBackup => "paper backup",
+            Field::Shares => "shares",
+            Field::PacketData => "OpenPGP data",
+            Field::Date => "date",
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingInput(field) => write!(f, "The {} is missing", field),
+            Error::MalformedArmor(field, e) => write!(f, "The {} is malformed: {}", field, e),
+            Error::WrongKeyType { field, found } => {
+                write!(f, "Expected a {} but found {}", field, found)
+            }
+            Error::WrongPassphrase => write!(f, "Wrong passphrase"),
+            Error::NoMatchingKey => write!(f, "This message is not encrypted for your key"),
+            Error::ExpiredKey(field) => write!(f, "The {} has expired", field),
+            Error::RevokedKey(field) => write!(f, "The {} has been revoked", field),
+            Error::UnsupportedAlgorithm(field, e) => {
+                write!(f, "The {} uses an unsupported algorithm: {}", field, e)
+            }
+            Error::InvalidSignedMessage(e) => write!(f, "Invalid signed message: {}", e),
+            Error::BadSignature(e) => write!(f, "Signature verification failed: {}", e),
+            Error::InvalidUtf8(e) => write!(f, "Error converting message to string: {}", e),
+            Error::InvalidDate(e) => write!(f, "Invalid date \"{}\"", e),
+            Error::GenerateKeys(e) => write!(f, "Error generating keys: {}", e),
+            Error::Encrypt(e) => write!(f, "Error encrypting message: {}", e),
+            Error::Decrypt(e) => write!(f, "Error decrypting message: {}", e),
+            Error::Sign(e) => write!(f, "Error signing message: {}", e),
+            Error::PaperBackup(e) => write!(f, "Paper backup error: {}", e),
+            Error::SecretSharing(e) => write!(f, "Secret sharing error: {}", e),
+            Error::Packets(e) => write!(f, "Error listing packets: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
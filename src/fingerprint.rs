@@ -0,0 +1,161 @@
+//! Fingerprint presentation helpers: grouped hex, the PGP word list and
+//! `OPENPGP4FPR:` URIs, plus parsing of fingerprints received out-of-band.
+
+/// Two-syllable words used for bytes at even positions of the fingerprint.
+const EVEN_WORDS: [&str; 256] = [
+    "aardvark", "absurd", "accrue", "acme", "adrift", "adult", "afflict", "ahead", "aimless",
+    "Algol", "allow", "alone", "ammo", "ancient", "apple", "artist", "assume", "Athens", "atlas",
+    "Aztec", "baboon", "backfield", "backward", "banjo", "beaming", "bedlamp", "beehive", "beeswax",
+    "befriend", "Belfast", "berserk", "billiard", "bison", "blackjack", "blockade", "blowtorch",
+    "bluebird", "bombast", "bookshelf", "brackish", "breadline", "breakup", "brickyard",
+    "briefcase", "Burbank", "button", "buzzard", "cement", "chairlift", "chatter", "checkup",
+    "chisel", "choking", "chopper", "Christmas", "clamshell", "classic", "classroom", "cleanup",
+    "clockwork", "cobra", "commence", "concert", "cowbell", "crackdown", "cranky", "crowfoot",
+    "crucial", "crumpled", "crusade", "cubic", "dashboard", "deadbolt", "deckhand", "dogsled",
+    "dragnet", "drainage", "dreadful", "drifter", "dropper", "drumbeat", "drunken", "Dupont",
+    "dwelling", "eating", "edict", "egghead", "eightball", "endorse", "endow", "enlist", "erase",
+    "escape", "exceed", "eyeglass", "eyetooth", "facial", "fallout", "flagpole", "flatfoot",
+    "flytrap", "fracture", "framework", "freedom", "frighten", "gazelle", "Geiger", "glitter",
+    "glucose", "goggles", "goldfish", "gremlin", "guidance", "hamlet", "highchair", "hockey",
+    "indoors", "indulge", "inverse", "involve", "island", "jawbone", "keyboard", "kickoff", "kiwi",
+    "klaxon", "locale", "lockup", "merit", "minnow", "miser", "Mohawk", "mural", "music",
+    "necklace", "Neptune", "newborn", "nightbird", "Oakland", "obtuse", "offload", "optic", "orca",
+    "payday", "peachy", "pheasant", "physique", "playhouse", "Pluto", "preclude", "prefer",
+    "preshrunk", "printer", "prowler", "pupil", "puppy", "python", "quadrant", "quiver", "quota",
+    "ragtime", "ratchet", "rebirth", "reform", "regain", "reindeer", "rematch", "repay", "retouch",
+    "revenge", "reward", "rhythm", "ribcage", "ringbolt", "robust", "rocker", "ruffled", "sailboat",
+    "sawdust", "scallion", "scenic", "scorecard", "Scotland", "seabird", "select", "sentence",
+    "shadow", "shamrock", "showgirl", "skullcap", "skydive", "slingshot", "slowdown", "snapline",
+    "snapshot", "snowcap", "snowslide", "solo", "southward", "soybean", "spaniel", "spearhead",
+    "spellbind", "spheroid", "spigot", "spindle", "spyglass", "stagehand", "stagnate", "stairway",
+    "standard", "stapler", "steamship", "sterling", "stockman", "stopwatch", "stormy", "sugar",
+    "surmount", "suspense", "sweatband", "swelter", "tactics", "talon", "tapeworm", "tempest",
+    "tiger", "tissue", "tonic", "topmost", "tracker", "transit", "trauma", "treadmill", "Trojan",
+    "trouble", "tumor", "tunnel", "tycoon", "uncut", "unearth", "unwind", "uproot", "upset",
+    "upshot", "vapor", "village", "virus", "Vulcan", "waffle", "wallet", "watchword", "wayside",
+    "willow", "woodlark", "Zulu",
+];
+
+/// Three-syllable words used for bytes at odd positions of the fingerprint.
+const ODD_WORDS: [&str; 256] = [
+    "adroitness", "adviser", "aftermath", "aggregate", "alkali", "almighty", "amulet", "amusement",
+    "antenna", "applicant", "Apollo", "armistice", "article", "asteroid", "Atlantic", "atmosphere",
+    "autopsy", "Babylon", "backwater", "barbecue", "belowground", "bifocals", "bodyguard",
+    "bookseller", "borderline", "bottomless", "Bradbury", "bravado", "Brazilian", "breakaway",
+    "Burlington", "businessman", "butterfat", "Camelot", "candidate", "cannonball", "Capricorn",
+    "caravan", "caretaker", "celebrate", "cellulose", "certify", "chambermaid", "Cherokee",
+    "Chicago", "clergyman", "coherence", "combustion", "commando", "company", "component",
+    "concurrent", "confidence", "conformist", "congregate", "consensus", "consulting", "corporate",
+    "corrosion", "councilman", "crossover", "crucifix", "cumbersome", "customer", "Dakota",
+    "decadence", "December", "decimal", "designing", "detector", "detergent", "determine",
+    "dictator", "dinosaur", "direction", "disable", "disbelief", "disruptive", "distortion",
+    "document", "embezzle", "enchanting", "enrollment", "enterprise", "equation", "equipment",
+    "escapade", "Eskimo", "everyday", "examine", "existence", "exodus", "fascinate", "filament",
+    "finicky", "forever", "fortitude", "frequency", "gadgetry", "Galveston", "getaway", "glossary",
+    "gossamer", "graduate", "gravity", "guitarist", "hamburger", "Hamilton", "handiwork",
+    "hazardous", "headwaters", "hemisphere", "hesitate", "hideaway", "holiness", "hurricane",
+    "hydraulic", "impartial", "impetus", "inception", "indigo", "inertia", "infancy", "inferno",
+    "informant", "insincere", "insurgent", "integrate", "intention", "inventive", "Istanbul",
+    "Jamaica", "Jupiter", "leprosy", "letterhead", "liberty", "maritime", "matchmaker", "maverick",
+    "Medusa", "megaton", "microscope", "microwave", "midsummer", "millionaire", "miracle",
+    "misnomer", "molasses", "molecule", "Montana", "monument", "mosquito", "narrative", "nebula",
+    "newsletter", "Norwegian", "October", "Ohio", "onlooker", "opulent", "Orlando", "outfielder",
+    "Pacific", "pandemic", "Pandora", "paperweight", "paragon", "paragraph", "paramount",
+    "passenger", "pedigree", "Pegasus", "penetrate", "perceptive", "performance", "pharmacy",
+    "phonetic", "photograph", "pioneer", "pocketful", "politeness", "positive", "potato",
+    "processor", "provincial", "proximate", "puberty", "publisher", "pyramid", "quantity",
+    "racketeer", "rebellion", "recipe", "recover", "repellent", "replica", "reproduce", "resistor",
+    "responsive", "retraction", "retrieval", "retrospect", "revenue", "revival", "revolver",
+    "sandalwood", "sardonic", "Saturday", "savagery", "scavenger", "sensation", "sociable",
+    "souvenir", "specialist", "speculate", "stethoscope", "stupendous", "supportive", "surrender",
+    "suspicious", "sympathy", "tambourine", "telephone", "therapist", "tobacco", "tolerance",
+    "tomorrow", "torpedo", "tradition", "travesty", "trombonist", "truncated", "typewriter",
+    "ultimate", "undaunted", "underfoot", "unicorn", "unify", "universe", "unravel", "upcoming",
+    "vacancy", "vagabond", "vertigo", "Virginia", "visitor", "vocalist", "voyager", "warranty",
+    "Waterloo", "whimsical", "Wichita", "Wilmington", "Wyoming", "yesteryear", "Yucatan",
+];
+
+/// Uppercase hex without separators, as used in `OPENPGP4FPR:` URIs.
+pub fn to_hex(fingerprint: &[u8]) -> String {
+    fingerprint.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Hex split into blocks of four characters with a wider gap in the middle,
+/// matching the way `gpg --fingerprint` prints it.
+pub fn to_grouped_hex(fingerprint: &[u8]) -> String {
+    let hex = to_hex(fingerprint);
+    let groups: Vec<&str> = hex
+        .as_bytes()
+        .chunks(4)
+        .map(|c| std::str::from_utf8(c).unwrap_or_default())
+        .collect();
+    let half = groups.len() / 2;
+    if groups.len() > 2 && groups.len() % 2 == 0 {
+        format!("{}  {}", groups[..half].join(" "), groups[half..].join(" "))
+    } else {
+        groups.join(" ")
+    }
+}
+
+/// Encodes the fingerprint with the PGP word list, alternating between the
+/// even and odd lists so that swapped or dropped words are detectable.
+pub fn to_words(fingerprint: &[u8]) -> Vec<&'static str> {
+    fingerprint
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            if i % 2 == 0 {
+                EVEN_WORDS[*b as usize]
+            } else {
+                ODD_WORDS[*b as usize]
+            }
+        })
+        .collect()
+}
+
+pub fn openpgp4fpr_uri(fingerprint: &[u8]) -> String {
+    format!("OPENPGP4FPR:{}", to_hex(fingerprint))
+}
+
+/// Parses a fingerprint pasted by the user. Accepts hex with any spacing,
+/// colons or a `0x` prefix, an `OPENPGP4FPR:` URI, or PGP words.
+pub fn parse(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix("OPENPGP4FPR:")
+        .or_else(|| input.strip_prefix("openpgp4fpr:"))
+        .unwrap_or(input);
+
+    parse_hex(input).or_else(|| parse_words(&input.split_whitespace().collect::<Vec<_>>()))
+}
+
+fn parse_hex(input: &str) -> Option<Vec<u8>> {
+    let hex: String = input
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect();
+    if hex.is_empty() || hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn parse_words(words: &[&str]) -> Option<Vec<u8>> {
+    if words.is_empty() {
+        return None;
+    }
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let list = if i % 2 == 0 { &EVEN_WORDS } else { &ODD_WORDS };
+            list.iter()
+                .position(|w| w.eq_ignore_ascii_case(word))
+                .map(|p| p as u8)
+        })
+        .collect()
+}
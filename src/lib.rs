@@ -0,0 +1,25 @@
+//! UI-independent core of Another PGP Tool. Every front end goes through
+//! [`crypto`], so the operations can be exercised without a window.
+
+pub mod armor;
+pub mod audit;
+pub mod compression;
+pub mod crypto;
+pub mod detect;
+mod error;
+pub mod fingerprint;
+pub mod literal;
+pub mod merge;
+pub mod packets;
+pub mod paperkey;
+pub mod preferences;
+pub mod qr;
+mod rfc9580;
+pub mod shamir;
+pub mod symmetric;
+pub mod wkd;
+
+pub use error::{Error, Field, Result};
+/// Buffer that is wiped when dropped, used for secret keys, passphrases and
+/// decrypted text.
+pub use zeroize::Zeroizing;
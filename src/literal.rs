@@ -0,0 +1,211 @@
+//! Literal data packet (RFC 4880, section 5.9): the file name, date and
+//! data format stored next to the content of a message.
+
+use std::{fmt, str::FromStr};
+
+use pgp::native::{packet::LiteralData, ser::Serialize, types::Version, Message};
+use web_time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+/// Longest file name, in characters, suggested when saving a message.
+const MAX_SAFE_FILE_NAME: usize = 100;
+
+/// How the content of a literal data packet is to be interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DataMode {
+    #[default]
+    Binary,
+    /// Text with CRLF line endings.
+    Text,
+    /// UTF-8 text with CRLF line endings.
+    Utf8,
+}
+
+impl DataMode {
+    pub const ALL: [DataMode; 3] = [DataMode::Binary, DataMode::Text, DataMode::Utf8];
+
+    /// Format octet stored in the packet.
+    fn octet(self) -> u8 {
+        match self {
+            DataMode::Binary => b'b',
+            DataMode::Text => b't',
+            DataMode::Utf8 => b'u',
+        }
+    }
+
+    /// Unknown formats such as MIME are handled as binary.
+    fn from_octet(octet: u8) -> Self {
+        match octet {
+            b't' => DataMode::Text,
+            b'u' => DataMode::Utf8,
+            _ => DataMode::Binary,
+        }
+    }
+
+    /// Short name, as accepted by [`DataMode::from_str`].
+    pub fn name(self) -> &'static str {
+        match self {
+            DataMode::Binary => "binary",
+            DataMode::Text => "text",
+            DataMode::Utf8 => "utf8",
+        }
+    }
+}
+
+impl fmt::Display for DataMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DataMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DataMode::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown data mode \"{}\", use binary, text or utf8", s))
+    }
+}
+
+/// Metadata written into the literal data packet when encrypting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LiteralOptions {
+    /// At most 255 bytes. Empty by default, as anyone who can decrypt the
+    /// message sees it.
+    pub file_name: String,
+    /// Seconds since the Unix epoch. `None` stores the current time and
+    /// `Some(0)` stores no date at all.
+    pub date: Option<u32>,
+    pub mode: DataMode,
+}
+
+/// Metadata read from the literal data packet of a decrypted message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LiteralInfo {
+    pub file_name: String,
+    /// Seconds since the Unix epoch, 0 when the sender stored no date.
+    pub date: u32,
+    pub mode: DataMode,
+}
+
+impl LiteralInfo {
+    /// The stored file name made safe to suggest when saving: the sender
+    /// chooses it, so path separators, `..`, control and bidirectional
+    /// formatting characters are removed and long names are shortened,
+    /// keeping their extension. `None` when nothing is left.
+    pub fn safe_file_name(&self) -> Option<String> {
+        let mut name = String::new();
+        for c in self.file_name.chars() {
+            let unsafe_char = c.is_control()
+                || matches!(
+                    c,
+                    '/' | '\\' | ':' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+                );
+            if unsafe_char || (c == '.' && name.ends_with('.')) {
+                continue;
+            }
+            name.push(c);
+        }
+        let name = name.trim_matches(|c: char| c == '.' || c.is_whitespace());
+        if name.is_empty() {
+            return None;
+        }
+        if name.chars().count() <= MAX_SAFE_FILE_NAME {
+            return Some(name.to_string());
+        }
+        let extension = name
+            .rfind('.')
+            .map(|i| &name[i..])
+            .filter(|extension| extension.chars().count() <= 16)
+            .unwrap_or_default();
+        let stem = name.chars().take(MAX_SAFE_FILE_NAME - extension.chars().count());
+        Some(stem.chain(extension.chars()).collect())
+    }
+}
+
+/// Builds the literal data message holding `data`. Text modes get CRLF line
+/// endings, as the standard requires.
+pub(crate) fn message(data: &[u8], options: &LiteralOptions) -> Result<Message, String> {
+    let file_name = options.file_name.as_bytes();
+    if file_name.len() > 255 {
+        return Err("The file name is longer than 255 bytes".to_string());
+    }
+    let date = match options.date {
+        Some(date) => date,
+        None => now()?,
+    };
+
+    let mut body = Zeroizing::new(Vec::with_capacity(6 + file_name.len() + data.len()));
+    body.push(options.mode.octet());
+    body.push(file_name.len() as u8);
+    body.extend_from_slice(file_name);
+    body.extend_from_slice(&date.to_be_bytes());
+    match options.mode {
+        DataMode::Binary => body.extend_from_slice(data),
+        DataMode::Text | DataMode::Utf8 => {
+            for (i, &byte) in data.iter().enumerate() {
+                if byte == b'\n' && (i == 0 || data[i - 1] != b'\r') {
+                    body.push(b'\r');
+                }
+                body.push(byte);
+            }
+        }
+    }
+    LiteralData::from_slice(Version::New, &body)
+        .map(Message::Literal)
+        .map_err(|e| e.to_string())
+}
+
+/// Takes the literal data packet of a decrypted and decompressed message
+/// apart. Text modes get their line endings turned back into LF.
+pub(crate) fn read(msg: &Message) -> Result<(LiteralInfo, Zeroizing<Vec<u8>>), String> {
+    let literal = match msg {
+        Message::Literal(literal) => literal,
+        Message::Signed {
+            message: Some(inner),
+            ..
+        } => return read(inner),
+        _ => return Err("Message contains no literal data".to_string()),
+    };
+    let body = Zeroizing::new(literal.to_bytes().map_err(|e| e.to_string())?);
+    let (&mode, rest) = body.split_first().ok_or("Literal data packet is empty")?;
+    let (&name_len, rest) = rest.split_first().ok_or("Literal data packet is truncated")?;
+    let name_len = usize::from(name_len);
+    if rest.len() < name_len + 4 {
+        return Err("Literal data packet is truncated".to_string());
+    }
+    let (file_name, rest) = rest.split_at(name_len);
+    let (date, data) = rest.split_at(4);
+
+    let mode = DataMode::from_octet(mode);
+    let data = match mode {
+        DataMode::Binary => Zeroizing::new(data.to_vec()),
+        DataMode::Text | DataMode::Utf8 => {
+            let mut lf = Zeroizing::new(Vec::with_capacity(data.len()));
+            for (i, &byte) in data.iter().enumerate() {
+                if byte != b'\r' || data.get(i + 1) != Some(&b'\n') {
+                    lf.push(byte);
+                }
+            }
+            lf
+        }
+    };
+    let info = LiteralInfo {
+        file_name: String::from_utf8_lossy(file_name).into_owned(),
+        date: u32::from_be_bytes([date[0], date[1], date[2], date[3]]),
+        mode,
+    };
+    Ok((info, data))
+}
+
+/// The current time in seconds since the Unix epoch. `web_time` falls back
+/// to the browser clock where `std::time::SystemTime` is unavailable.
+pub(crate) fn now() -> Result<u32, String> {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+    u32::try_from(seconds).map_err(|_| "The current time does not fit an OpenPGP date".to_string())
+}
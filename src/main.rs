@@ -1,13 +1,32 @@
 #![windows_subsystem = "windows"]
 
-use std::{env, path::Path};
-
-use dioxus::desktop::tao::window::Icon;
+#[cfg(feature = "desktop")]
+use std::env;
+use std::time::Duration;
+
+use another_pgp_tool::audit::{self, FixIt, Severity};
+use another_pgp_tool::compression::Compression;
+use another_pgp_tool::crypto::KeyProfile;
+use another_pgp_tool::detect::{self, Content};
+use another_pgp_tool::literal::{DataMode, LiteralInfo, LiteralOptions};
+use another_pgp_tool::symmetric::{HashAlgorithm, SymmetricKeyAlgorithm};
+use another_pgp_tool::{
+    armor, crypto, fingerprint, packets, preferences, qr, shamir, symmetric, Error, Field,
+    Zeroizing,
+};
+#[cfg(feature = "desktop")]
+use dioxus::desktop::tao::{event::Event, window::Icon};
+#[cfg(feature = "desktop")]
 use dioxus::desktop::{Config, WindowBuilder, WindowCloseBehaviour};
 use dioxus::prelude::*;
-use pgp::{decrypt, encrypt, gen_key_pair, read_sig_from_bytes, sign, utils, verify};
+
+mod clipboard;
+#[cfg(feature = "desktop")]
+mod session_dir;
+mod share;
 
 const FAVICON_BYTES: &[u8] = include_bytes!("../assets/favicon.ico");
+#[cfg(feature = "desktop")]
 const ICON_BYTES: &[u8] = include_bytes!("../icons/icon.png");
 const MAIN_CSS: &[u8] = include_bytes!("../assets/main.css");
 
@@ -18,7 +37,28 @@ enum ActiveTab {
     Decrypt,
     Sign,
     Verify,
+    Backup,
+    Shares,
+    Inspect,
+    Armor,
+}
+
+impl ActiveTab {
+    fn title(self) -> &'static str {
+        match self {
+            ActiveTab::Generate => "Generate",
+            ActiveTab::Encrypt => "Encrypt",
+            ActiveTab::Decrypt => "Decrypt",
+            ActiveTab::Sign => "Sign",
+            ActiveTab::Verify => "Verify",
+            ActiveTab::Backup => "Backup",
+            ActiveTab::Shares => "Shares",
+            ActiveTab::Inspect => "Inspect",
+            ActiveTab::Armor => "Armor",
+        }
+    }
 }
+
 #[derive(Clone, PartialEq)]
 struct Notification {
     message: String,
@@ -26,6 +66,21 @@ struct Notification {
     id: u32,
 }
 
+/// Text handed to a tab from outside, e.g. through the share sheet or a
+/// dropped file.
+struct IncomingText {
+    tab: ActiveTab,
+    field: Field,
+    text: Zeroizing<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum QrContent {
+    Fingerprint,
+    FingerprintWkd,
+    PublicKey,
+}
+
 #[derive(Clone, PartialEq)]
 enum NotificationType {
     Error,
@@ -36,10 +91,25 @@ enum NotificationType {
 static ACTIVETAB: GlobalSignal<ActiveTab> = Signal::global(|| ActiveTab::Generate);
 static NOTIFICATIONS: GlobalSignal<Vec<Notification>> = Signal::global(Vec::new);
 static NEXT_ID: GlobalSignal<u32> = Signal::global(|| 0);
+static INCOMING_TEXT: GlobalSignal<Vec<IncomingText>> = Signal::global(Vec::new);
+/// Seconds after which copied secrets are removed from the clipboard, 0
+/// keeps them.
+static CLIPBOARD_CLEAR_SECONDS: GlobalSignal<u64> = Signal::global(|| 30);
 
 fn main() {
-    let temp_path = Path::new(env::temp_dir().to_str().unwrap()).join("another_pgp_tool");
-    env::set_var("WEBVIEW2_USER_DATA_FOLDER", temp_path.to_str().unwrap());
+    #[cfg(feature = "desktop")]
+    launch_desktop();
+
+    // Web (WASM) and mobile builds need no platform configuration.
+    #[cfg(not(feature = "desktop"))]
+    dioxus::launch(App);
+}
+
+#[cfg(feature = "desktop")]
+fn launch_desktop() {
+    let session = session_dir::SessionDir::create().expect("Failed to create data directory");
+    env::set_var("WEBVIEW2_USER_DATA_FOLDER", session.path());
+    let data_directory = session.path().to_path_buf();
 
     let img = image::load_from_memory(ICON_BYTES).expect("Failed to load image from memory");
     let rgba_img = img.into_rgba8();
@@ -55,7 +125,14 @@ fn main() {
         .with_cfg(
             Config::new()
                 .with_close_behaviour(WindowCloseBehaviour::LastWindowExitsApp)
-                .with_data_directory(temp_path)
+                .with_data_directory(data_directory)
+                .with_custom_event_handler(move |event, _| {
+                    if let Event::LoopDestroyed = event {
+                        // The window is gone, so failures cannot be shown;
+                        // the next launch wipes what is left or reports it.
+                        let _ = session.wipe();
+                    }
+                })
                 .with_disable_context_menu(true)
                 .with_icon(icon)
                 .with_window(WindowBuilder::new().with_title("Yet Another PGP Tool"))
@@ -66,15 +143,61 @@ fn main() {
 
 #[component]
 fn App() -> Element {
+    let mut dragging = use_signal(|| false);
+
+    use_hook(|| {
+        #[cfg(feature = "desktop")]
+        if !session_dir::leftovers().is_empty() {
+            show_message(
+                format!(
+                    "Webview data from {} earlier session(s) is still on disk. \
+                     Close other running instances and restart to remove it.",
+                    session_dir::leftovers().len()
+                ),
+                Some(NotificationType::Error),
+            );
+        }
+        if let Some(text) = share::received_text() {
+            open_data(text.as_bytes());
+        }
+    });
+
     rsx! {
         document::Link {
             rel: "icon",
             href: format!("data:image/x-icon;base64,{}", base64::encode(FAVICON_BYTES)),
         }
+        document::Meta {
+            name: "viewport",
+            content: "width=device-width, initial-scale=1, viewport-fit=cover",
+        }
         style { "{std::str::from_utf8(MAIN_CSS).unwrap_or_default()}" }
-        div { class: "app-container",
+        div {
+            class: if dragging() { "app-container dragging" } else { "app-container" },
+            ondragover: move |evt| {
+                evt.prevent_default();
+                dragging.set(true);
+            },
+            ondragleave: move |_| dragging.set(false),
+            ondrop: move |evt| async move {
+                evt.prevent_default();
+                dragging.set(false);
+                let Some(file_engine) = evt.files() else {
+                    return;
+                };
+                for name in file_engine.files() {
+                    match file_engine.read_file(&name).await {
+                        Some(data) => open_data(&data),
+                        None => show_message(
+                            format!("Error reading {}", name),
+                            Some(NotificationType::Error),
+                        ),
+                    }
+                }
+            },
             Header {}
             TabNavigation {}
+            UniversalInput {}
             TabContent {}
             NotificationContainer {}
         }
@@ -128,6 +251,26 @@ fn Header() -> Element {
                 span { class: "header-icon", "🔐" }
                 "Yet Another PGP Tool"
             }
+            label { class: "clipboard-setting",
+                "Clear copied secrets after:"
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| {
+                        *CLIPBOARD_CLEAR_SECONDS.write() = evt.value().parse().unwrap_or(30)
+                    },
+                    for seconds in [10u64, 30, 60, 300, 0] {
+                        option {
+                            value: "{seconds}",
+                            selected: *CLIPBOARD_CLEAR_SECONDS.read() == seconds,
+                            if seconds == 0 {
+                                "Never"
+                            } else {
+                                "{seconds} s"
+                            }
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -168,6 +311,80 @@ fn TabNavigation() -> Element {
                 span { class: "tab-icon", "✍" }
                 span { class: "tab-text", "Sign" }
             }
+            button {
+                class: if *active_tab == ActiveTab::Backup { "tab-button active" } else { "tab-button" },
+                onclick: move |_| *ACTIVETAB.write() = ActiveTab::Backup,
+                span { class: "tab-icon", "📄" }
+                span { class: "tab-text", "Backup" }
+            }
+            button {
+                class: if *active_tab == ActiveTab::Shares { "tab-button active" } else { "tab-button" },
+                onclick: move |_| *ACTIVETAB.write() = ActiveTab::Shares,
+                span { class: "tab-icon", "🧩" }
+                span { class: "tab-text", "Shares" }
+            }
+            button {
+                class: if *active_tab == ActiveTab::Inspect { "tab-button active" } else { "tab-button" },
+                onclick: move |_| *ACTIVETAB.write() = ActiveTab::Inspect,
+                span { class: "tab-icon", "🔍" }
+                span { class: "tab-text", "Inspect" }
+            }
+            button {
+                class: if *active_tab == ActiveTab::Armor { "tab-button active" } else { "tab-button" },
+                onclick: move |_| *ACTIVETAB.write() = ActiveTab::Armor,
+                span { class: "tab-icon", "🧾" }
+                span { class: "tab-text", "Armor" }
+            }
+        }
+    }
+}
+
+/// Input that accepts anything OpenPGP, tells what it is and opens it in
+/// the right tab.
+#[component]
+fn UniversalInput() -> Element {
+    let mut input = use_secret();
+    let detected = {
+        let text = input.read();
+        if text.trim().is_empty() {
+            None
+        } else {
+            let content = detect::armored_content(&text);
+            Some((content_description(content), destination(content)))
+        }
+    };
+
+    rsx! {
+        details { class: "universal-input",
+            summary { "Not sure which tab? Paste it here" }
+            textarea {
+                class: "message-textarea",
+                value: input.read().as_str(),
+                oninput: move |evt| input.set(Zeroizing::new(evt.value())),
+                rows: 4,
+                cols: 50,
+                placeholder: "Paste a key, message or signature here...",
+            }
+            div { class: "clipboard-actions",
+                button {
+                    class: "clipboard-button",
+                    onclick: move |_| paste_from_clipboard(input),
+                    "Paste"
+                }
+            }
+            if let Some((description, (tab, _))) = detected {
+                div { class: "detected-content",
+                    span { "Detected: {description}" }
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| {
+                            open_data(input.read().as_bytes());
+                            input.set(Zeroizing::default());
+                        },
+                        "Open in {tab.title()}"
+                    }
+                }
+            }
         }
     }
 }
@@ -193,6 +410,18 @@ fn TabContent() -> Element {
                 ActiveTab::Sign => rsx! {
                     SignMessageTab {}
                 },
+                ActiveTab::Backup => rsx! {
+                    PaperBackupTab {}
+                },
+                ActiveTab::Shares => rsx! {
+                    SecretSharingTab {}
+                },
+                ActiveTab::Inspect => rsx! {
+                    InspectTab {}
+                },
+                ActiveTab::Armor => rsx! {
+                    ArmorTab {}
+                },
             }
         }
     }
@@ -200,46 +429,49 @@ fn TabContent() -> Element {
 
 #[component]
 fn GenerateKeysTab() -> Element {
-    let mut private_key = use_signal(String::new);
+    let mut private_key = use_secret();
     let mut public_key = use_signal(String::new);
+    let mut profile = use_signal(KeyProfile::default);
+    let mut error = use_signal(|| None::<Error>);
 
     let generate_keys = move |_| async move {
-        let (priv_key, pub_key) = match gen_key_pair("", "").await {
-            Ok(res) => res,
-            Err(e) => {
-                show_message(
-                    format!("Error generating keys: {}", e),
-                    Some(NotificationType::Error),
-                );
-                return;
-            }
-        };
-        private_key.set(match priv_key.to_armored_string(None) {
-            Ok(s) => s,
-            Err(e) => {
-                show_message(
-                    format!("Private key not valid string: {}", e),
-                    Some(NotificationType::Error),
-                );
-                return;
-            }
-        });
-        public_key.set(match pub_key.to_armored_string(None) {
-            Ok(s) => s,
-            Err(e) => {
-                show_message(
-                    format!("Public key not valid string: {}", e),
-                    Some(NotificationType::Error),
-                );
-                return;
+        error.set(None);
+        match crypto::generate_keypair_with_profile(*profile.read()).await {
+            Ok(keys) => {
+                private_key.set(keys.secret_key);
+                public_key.set(keys.public_key);
             }
-        });
+            Err(e) => show_error(error, e),
+        }
     };
 
     rsx! {
         div { class: "tab-panel",
             h2 { "Generate PGP Keys" }
 
+            div { class: "form-group share-settings",
+                label {
+                    "Profile:"
+                    select {
+                        class: "qr-select",
+                        onchange: move |evt| profile.set(evt.value().parse().unwrap_or_default()),
+                        for option_profile in KeyProfile::ALL {
+                            option {
+                                key: "{option_profile}",
+                                value: "{option_profile}",
+                                selected: *profile.read() == option_profile,
+                                "{profile_name(option_profile)}"
+                            }
+                        }
+                    }
+                }
+                if *profile.read() == KeyProfile::Rfc9580 {
+                    div { class: "recipient-status",
+                        "v6 keys and the AEAD messages sent to them can only be read by RFC 9580 implementations, not by GnuPG."
+                    }
+                }
+            }
+
             div { class: "form-group",
                 button { class: "generate-button", onclick: generate_keys, "Generate Keys" }
             }
@@ -250,10 +482,17 @@ fn GenerateKeysTab() -> Element {
                     textarea {
                         class: "key-textarea",
                         readonly: true,
-                        value: private_key.read().clone(),
+                        value: private_key.read().as_str(),
                         rows: 10,
                         cols: 50,
                     }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| copy_to_clipboard(&private_key.read(), true),
+                            "Copy"
+                        }
+                    }
                 }
 
                 div { class: "key-section",
@@ -265,8 +504,17 @@ fn GenerateKeysTab() -> Element {
                         rows: 10,
                         cols: 50,
                     }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| copy_to_clipboard(&public_key.read(), false),
+                            "Copy"
+                        }
+                    }
                 }
             }
+
+            KeyInspector { public_key: public_key.read().clone() }
         }
     }
 }
@@ -274,45 +522,55 @@ fn GenerateKeysTab() -> Element {
 #[component]
 fn EncryptMessageTab() -> Element {
     let mut recipient_public_key = use_signal(String::new);
-    let mut plain_message = use_signal(String::new);
+    let mut plain_message = use_secret();
     let encrypted_message = use_signal(String::new);
+    let use_passphrase = use_signal(|| false);
+    let mut passphrase = use_secret();
+    let s2k_options = use_signal(symmetric::S2kOptions::default);
+    let literal_options = use_signal(LiteralOptions::default);
+    let custom_date = use_signal(|| None::<String>);
+    let compression = use_signal(|| None::<Compression>);
+    let allow_unusable = use_signal(|| false);
+    let mut error = use_signal(|| None::<Error>);
+    use_incoming_text(ActiveTab::Encrypt, Field::PublicKey, recipient_public_key);
+    use_incoming_text(ActiveTab::Encrypt, Field::Message, plain_message);
 
     let encrypt_message = move |_| {
-        to_owned![plain_message, recipient_public_key, encrypted_message];
+        to_owned![plain_message, recipient_public_key, encrypted_message, passphrase];
         async move {
-            let msg = plain_message.read().clone().as_bytes().to_vec();
-            let skey = match utils::read_pkey_from_string(recipient_public_key.read().clone()).await
-            {
-                Ok(s) => s,
+            error.set(None);
+            let literal = match literal_options_with_date(literal_options, custom_date) {
+                Ok(literal) => literal,
                 Err(e) => {
-                    show_message(
-                        format!("Error reading public key: {}", e),
-                        Some(NotificationType::Error),
-                    );
+                    show_error(error, e);
                     return;
                 }
             };
-            let encrypted_msg = match encrypt(vec![skey], msg).await {
-                Ok(s) => s,
-                Err(e) => {
-                    show_message(
-                        format!("Error encrypting message: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
-                }
+            let result = if *use_passphrase.read() {
+                crypto::encrypt_text_with_passphrase(
+                    &passphrase.read(),
+                    &plain_message.read(),
+                    *s2k_options.read(),
+                    &literal,
+                    compression.read().unwrap_or(Compression::Zlib),
+                )
+            } else {
+                let recipient = recipient_public_key.read().clone();
+                let plaintext = plain_message.read().clone();
+                let compression = *compression.read();
+                let allow_unusable = *allow_unusable.read();
+                crypto::encrypt_text(&recipient, &plaintext, &literal, compression, allow_unusable)
+                    .await
             };
 
-            encrypted_message.set(match String::from_utf8(encrypted_msg) {
-                Ok(s) => s,
-                Err(e) => {
-                    show_message(
-                        format!("Error converting encrypted message to string: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
+            match result {
+                Ok(s) => {
+                    encrypted_message.set(s);
+                    plain_message.set(Zeroizing::default());
+                    passphrase.set(Zeroizing::default());
                 }
-            });
+                Err(e) => show_error(error, e),
+            }
         }
     };
 
@@ -320,15 +578,57 @@ fn EncryptMessageTab() -> Element {
         div { class: "tab-panel",
             h2 { "Encrypt Message" }
 
-            div { class: "form-group",
-                label { "Recipient's Public Key:" }
-                textarea {
-                    class: "key-textarea",
-                    value: recipient_public_key.read().clone(),
-                    oninput: move |evt| recipient_public_key.set(evt.value().clone()),
-                    rows: 8,
-                    cols: 50,
-                    placeholder: "Paste recipient's public key here...",
+            EncryptionModeSelect { use_passphrase }
+
+            if *use_passphrase.read() {
+                div { class: "form-group",
+                    label { "Passphrase:" }
+                    input {
+                        class: "fingerprint-input",
+                        r#type: "password",
+                        value: passphrase.read().as_str(),
+                        oninput: move |evt| passphrase.set(Zeroizing::new(evt.value())),
+                        placeholder: "Passphrase the recipient will use to decrypt...",
+                    }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| paste_from_clipboard(passphrase),
+                            "Paste"
+                        }
+                    }
+                    FieldError { error, field: Field::Passphrase }
+                }
+                S2kSettings { options: s2k_options }
+            } else {
+                div { class: "form-group",
+                    label { "Recipients' Public Keys:" }
+                    textarea {
+                        class: "key-textarea",
+                        value: recipient_public_key.read().clone(),
+                        oninput: move |evt| recipient_public_key.set(evt.value()),
+                        rows: 8,
+                        cols: 50,
+                        placeholder: "Paste one or more recipients' public keys here...",
+                    }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| paste_from_clipboard(recipient_public_key),
+                            "Paste"
+                        }
+                    }
+                    FieldError { error, field: Field::PublicKey }
+                    QrImport {
+                        on_import: move |content| {
+                            if let Some(key) = public_key_from_qr(content) {
+                                recipient_public_key.set(key);
+                            }
+                        },
+                    }
+                    KeyInspector { public_key: recipient_public_key.read().clone() }
+                    RecipientKeyStatus { public_keys: recipient_public_key.read().clone(), allow_unusable }
+                    NegotiatedAlgorithms { public_keys: recipient_public_key.read().clone() }
                 }
             }
 
@@ -336,12 +636,32 @@ fn EncryptMessageTab() -> Element {
                 label { "Message to Encrypt:" }
                 textarea {
                     class: "message-textarea",
-                    value: plain_message.read().clone(),
-                    oninput: move |evt| plain_message.set(evt.value().clone()),
+                    value: plain_message.read().as_str(),
+                    oninput: move |evt| plain_message.set(Zeroizing::new(evt.value())),
                     rows: 5,
                     cols: 50,
                     placeholder: "Type your message here...",
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(plain_message),
+                        "Paste"
+                    }
+                }
+                details { class: "literal-settings",
+                    summary { "File name, date, format and compression" }
+                    LiteralSettings { options: literal_options, custom_date }
+                    FieldError { error, field: Field::Date }
+                    CompressionSelect {
+                        compression,
+                        default_label: if *use_passphrase.read() {
+                            "Default (ZLIB)"
+                        } else {
+                            "Recipient's preference"
+                        },
+                    }
+                }
             }
 
             div { class: "form-group",
@@ -357,6 +677,13 @@ fn EncryptMessageTab() -> Element {
                     rows: 8,
                     cols: 50,
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&encrypted_message.read(), false),
+                        "Copy"
+                    }
+                }
             }
         }
     }
@@ -364,46 +691,46 @@ fn EncryptMessageTab() -> Element {
 
 #[component]
 fn DecryptMessageTab() -> Element {
-    let mut private_key = use_signal(String::new);
+    let mut private_key = use_secret();
     let mut encrypted_message = use_signal(String::new);
-    let decrypted_message = use_signal(String::new);
+    let decrypted_message = use_secret();
+    let decrypted_literal = use_signal(|| None::<LiteralInfo>);
+    let use_passphrase = use_signal(|| false);
+    let mut passphrase = use_secret();
+    let mut error = use_signal(|| None::<Error>);
+    use_incoming_text(ActiveTab::Decrypt, Field::SecretKey, private_key);
+    use_incoming_text(ActiveTab::Decrypt, Field::EncryptedMessage, encrypted_message);
 
     let decrypt_message = move |_| {
-        to_owned![private_key, encrypted_message, decrypted_message];
+        to_owned![
+            private_key,
+            encrypted_message,
+            decrypted_message,
+            decrypted_literal,
+            passphrase
+        ];
         async move {
-            let encrypted_data = encrypted_message.read().clone().as_bytes().to_vec();
-            let skey = match utils::read_skey_from_string(private_key.read().clone()).await {
-                Ok(s) => s,
-                Err(e) => {
-                    show_message(
-                        format!("Error reading private key: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
-                }
+            error.set(None);
+            let result = if *use_passphrase.read() {
+                crypto::decrypt_text_with_passphrase(&passphrase.read(), &encrypted_message.read())
+            } else {
+                let skey = private_key.read().clone();
+                let encrypted = encrypted_message.read().clone();
+                crypto::decrypt_text(&skey, "", &encrypted).await
             };
 
-            let decrypted_msg = match decrypt(skey, "", encrypted_data).await {
-                Ok(s) => s,
-                Err(e) => {
-                    show_message(
-                        format!("Error decrypting message: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
+            match result {
+                Ok(decrypted) => {
+                    decrypted_message.set(decrypted.text);
+                    decrypted_literal.set(Some(decrypted.literal));
+                    private_key.set(Zeroizing::default());
+                    passphrase.set(Zeroizing::default());
                 }
-            };
-
-            decrypted_message.set(match String::from_utf8(decrypted_msg) {
-                Ok(s) => s,
                 Err(e) => {
-                    show_message(
-                        format!("Error converting decrypted message to string: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
+                    decrypted_literal.set(None);
+                    show_error(error, e);
                 }
-            });
+            }
         }
     };
 
@@ -411,15 +738,46 @@ fn DecryptMessageTab() -> Element {
         div { class: "tab-panel",
             h2 { "Decrypt Message" }
 
-            div { class: "form-group",
-                label { "Your Private Key:" }
-                textarea {
-                    class: "key-textarea",
-                    value: private_key.read().clone(),
-                    oninput: move |evt| private_key.set(evt.value().clone()),
-                    rows: 8,
-                    cols: 50,
-                    placeholder: "Paste your private key here...",
+            EncryptionModeSelect { use_passphrase }
+
+            if *use_passphrase.read() {
+                div { class: "form-group",
+                    label { "Passphrase:" }
+                    input {
+                        class: "fingerprint-input",
+                        r#type: "password",
+                        value: passphrase.read().as_str(),
+                        oninput: move |evt| passphrase.set(Zeroizing::new(evt.value())),
+                        placeholder: "Passphrase the message was encrypted with...",
+                    }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| paste_from_clipboard(passphrase),
+                            "Paste"
+                        }
+                    }
+                    FieldError { error, field: Field::Passphrase }
+                }
+            } else {
+                div { class: "form-group",
+                    label { "Your Private Key:" }
+                    textarea {
+                        class: "key-textarea",
+                        value: private_key.read().as_str(),
+                        oninput: move |evt| private_key.set(Zeroizing::new(evt.value())),
+                        rows: 8,
+                        cols: 50,
+                        placeholder: "Paste your private key here...",
+                    }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| paste_from_clipboard(private_key),
+                            "Paste"
+                        }
+                    }
+                    FieldError { error, field: Field::SecretKey }
                 }
             }
 
@@ -428,11 +786,20 @@ fn DecryptMessageTab() -> Element {
                 textarea {
                     class: "message-textarea",
                     value: encrypted_message.read().clone(),
-                    oninput: move |evt| encrypted_message.set(evt.value().clone()),
+                    oninput: move |evt| encrypted_message.set(evt.value()),
                     rows: 5,
                     cols: 50,
                     placeholder: "Paste the encrypted message here...",
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(encrypted_message),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::EncryptedMessage }
+                MessageRecipients { encrypted_message, private_key }
             }
 
             div { class: "form-group",
@@ -444,137 +811,292 @@ fn DecryptMessageTab() -> Element {
                 textarea {
                     class: "decrypted-textarea",
                     readonly: true,
-                    value: decrypted_message.read().clone(),
+                    value: decrypted_message.read().as_str(),
                     rows: 8,
                     cols: 50,
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&decrypted_message.read(), true),
+                        "Copy"
+                    }
+                    if decrypted_literal.read().is_some() {
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| save_decrypted(decrypted_message, decrypted_literal),
+                            "Save"
+                        }
+                    }
+                }
+                if let Some(literal) = &*decrypted_literal.read() {
+                    LiteralDetails { literal: literal.clone() }
+                }
             }
         }
     }
 }
 
+/// File name, date and format the sender stored with a decrypted message.
 #[component]
-fn VerifyMessageTab() -> Element {
-    let mut public_key = use_signal(String::new);
-    let mut signed_message = use_signal(String::new);
-    let mut verification_result = use_signal(String::new);
+fn LiteralDetails(literal: LiteralInfo) -> Element {
+    let date = match literal.date {
+        0 => "none".to_string(),
+        date => packets::format_time(date),
+    };
+    rsx! {
+        div { class: "literal-details",
+            if !literal.file_name.is_empty() {
+                span { "File name: {literal.file_name}" }
+            }
+            span { "Date: {date}" }
+            span { "Format: {data_mode_name(literal.mode)}" }
+        }
+    }
+}
 
-    let verify_message = move |_| {
-        let public_key = public_key.read().clone();
-        let signed_message = signed_message.read().clone();
+/// Saves the decrypted text, suggesting the file name the sender stored.
+async fn save_decrypted(text: Signal<Zeroizing<String>>, literal: Signal<Option<LiteralInfo>>) {
+    let file_name = literal
+        .read()
+        .as_ref()
+        .and_then(LiteralInfo::safe_file_name)
+        .unwrap_or_else(|| "decrypted.txt".to_string());
+    let text = text.read().clone();
+    match save_file(&file_name, "text/plain", text.as_bytes()).await {
+        Ok(true) => show_message(
+            format!("Saved {}", file_name),
+            Some(NotificationType::Success),
+        ),
+        Ok(false) => {}
+        Err(e) => show_message(
+            format!("Error saving file: {}", e),
+            Some(NotificationType::Error),
+        ),
+    }
+}
 
-        spawn(async move {
-            // Validate input presence
-            if public_key.trim().is_empty() || signed_message.trim().is_empty() {
-                verification_result
-                    .set("Error: Public key and signed message are required".to_string());
-                show_message(
-                    "Please provide both public key and signed message".to_string(),
-                    Some(NotificationType::Error),
-                );
-                return;
-            }
+/// Recipients of the pasted message, with the ones the loaded private key
+/// can decrypt for highlighted.
+#[component]
+fn MessageRecipients(
+    encrypted_message: ReadOnlySignal<String>,
+    private_key: ReadOnlySignal<Zeroizing<String>>,
+) -> Element {
+    let recipients = use_resource(move || async move {
+        let message = encrypted_message.read().clone();
+        let skey = private_key.read().clone();
+        crypto::check_recipients(&message, &skey).await.ok()
+    });
 
-            // Parse the signed message
-            let signature_start = match signed_message.find("-----BEGIN PGP SIGNATURE-----") {
-                Some(pos) => pos,
-                None => {
-                    verification_result.set("Error: Invalid signed message format".to_string());
-                    show_message(
-                        "Invalid signed message format".to_string(),
-                        Some(NotificationType::Error),
-                    );
-                    return;
-                }
-            };
+    let recipients = recipients.read();
+    let Some(Some(recipients)) = &*recipients else {
+        return rsx! {};
+    };
+    if recipients.keys.is_empty() && !recipients.passphrase {
+        return rsx! {};
+    }
+    let key_checked = recipients.keys.iter().any(|r| r.matches.is_some());
+    let anonymous = recipients.keys.iter().any(|r| r.anonymous);
+    let wrong_key = key_checked && !anonymous && !recipients.any_match();
 
-            let signature_end = match signed_message.find("-----END PGP SIGNATURE-----") {
-                Some(pos) => pos + "-----END PGP SIGNATURE-----".len(),
-                None => {
-                    verification_result.set("Error: Incomplete signature block".to_string());
-                    show_message(
-                        "Missing signature end marker".to_string(),
-                        Some(NotificationType::Error),
-                    );
-                    return;
+    rsx! {
+        div { class: "recipients",
+            label { "Encrypted for:" }
+            for (i , recipient) in recipients.keys.iter().enumerate() {
+                div {
+                    key: "{i}",
+                    class: if recipient.matches == Some(true) { "recipient recipient-match" } else { "recipient" },
+                    span { class: "fingerprint-hex", "{recipient.key_id}" }
+                    span { class: "recipient-status",
+                        if recipient.anonymous {
+                            "hidden recipient, only trying a key tells"
+                        } else if recipient.matches == Some(true) {
+                            "your key"
+                        } else if recipient.matches == Some(false) {
+                            "a different key"
+                        }
+                    }
+                }
+            }
+            if recipients.passphrase {
+                div { class: "recipient",
+                    span { class: "recipient-status", "a passphrase" }
+                }
+            }
+            if wrong_key {
+                div { class: "field-error",
+                    div { class: "field-error-message", "{Error::NoMatchingKey}" }
+                    div { class: "field-error-hint", "{Error::NoMatchingKey.suggestion()}" }
                 }
-            };
-
-            if signature_end <= signature_start {
-                verification_result.set("Error: Invalid signature block format".to_string());
-                return;
             }
+        }
+    }
+}
+
+/// Whether each pasted recipient key can be encrypted to, with the
+/// override for revoked and expired keys.
+#[component]
+fn RecipientKeyStatus(public_keys: ReadOnlySignal<String>, allow_unusable: Signal<bool>) -> Element {
+    let keys = use_resource(move || async move {
+        let keys = public_keys.read().clone();
+        crypto::check_recipient_keys(&keys).await.ok()
+    });
 
-            let signature_part = &signed_message[signature_start..signature_end];
-            let message_part = signed_message[..signature_start].trim();
+    let keys = keys.read();
+    let Some(Some(keys)) = &*keys else {
+        return rsx! {};
+    };
+    let unusable = keys.iter().any(|key| key.usability.needs_override());
 
-            // Handle PGP signed message header
-            let clean_message = if message_part.starts_with("-----BEGIN PGP SIGNED MESSAGE-----") {
-                let header_end = message_part.find("\n\n").unwrap_or(0);
-                if header_end > 0 {
-                    message_part[header_end + 2..].trim()
-                } else {
-                    message_part.trim()
+    rsx! {
+        div { class: "recipients",
+            for (i , key) in keys.iter().enumerate() {
+                div {
+                    key: "{i}",
+                    class: if key.usability.can_encrypt() { "recipient recipient-match" } else { "recipient recipient-unusable" },
+                    span { "{key.name}" }
+                    span { class: "recipient-status", "{recipient_key_status(&key.usability)}" }
                 }
-            } else {
-                message_part
-            };
-
-            // Process signature and public key
-            match read_sig_from_bytes(signature_part.as_bytes().to_vec()).await {
-                Ok(sig) => match utils::read_pkey_from_string(public_key).await {
-                    Ok(pkey) => match verify(pkey, sig, clean_message.as_bytes().to_vec()).await {
-                        Ok(_) => {
-                            verification_result.set(format!(
-                                "✓ Signature Valid\n\nVerified Message:\n{}",
-                                clean_message
-                            ));
-                            show_message(
-                                "Signature verified successfully!".to_string(),
-                                Some(NotificationType::Success),
-                            );
-                        }
-                        Err(e) => {
-                            verification_result.set("✗ Invalid Signature".to_string());
-                            show_message(
-                                format!("Signature verification failed: {}", e),
-                                Some(NotificationType::Error),
-                            );
-                        }
-                    },
-                    Err(e) => {
-                        verification_result.set("Error: Invalid public key".to_string());
-                        show_message(
-                            format!("Error reading public key: {}", e),
-                            Some(NotificationType::Error),
-                        );
+            }
+            if unusable {
+                label { class: "allow-unusable",
+                    input {
+                        r#type: "checkbox",
+                        checked: allow_unusable(),
+                        onchange: move |evt| allow_unusable.set(evt.checked()),
                     }
-                },
-                Err(e) => {
-                    verification_result.set("Error: Invalid signature format".to_string());
-                    show_message(
-                        format!("Error reading signature: {}", e),
-                        Some(NotificationType::Error),
-                    );
+                    "Encrypt to revoked or expired keys anyway"
                 }
             }
-        });
+        }
+    }
+}
+
+fn recipient_key_status(usability: &audit::Usability) -> String {
+    if usability.revoked {
+        "revoked, do not encrypt to it".to_string()
+    } else if let Some(expired) = usability.expired {
+        format!("expired on {}", packets::format_time(expired))
+    } else if usability.encryption_keys.is_empty() && !usability.expired_encryption_keys.is_empty() {
+        "its encryption subkeys have expired".to_string()
+    } else if usability.encryption_keys.is_empty() {
+        "cannot be encrypted to, no usable encryption subkey".to_string()
+    } else {
+        "ready to encrypt".to_string()
+    }
+}
+
+/// The algorithms every pasted recipient key accepts, with a warning for
+/// keys stuck on legacy ones.
+#[component]
+fn NegotiatedAlgorithms(public_keys: ReadOnlySignal<String>) -> Element {
+    let negotiated = use_resource(move || async move {
+        let keys = public_keys.read().clone();
+        crypto::negotiate_algorithms(&keys).await.ok()
+    });
+
+    let negotiated = negotiated.read();
+    let Some(Some(negotiated)) = &*negotiated else {
+        return rsx! {};
     };
 
     rsx! {
-        div { class: "tab-panel",
-            h2 { "Verify Message" }
-
-            div { class: "form-group",
+        div { class: "negotiated",
+            div { "Algorithms: {negotiated.summary()}" }
+            div { class: "recipient-status", "{aead_status(negotiated.aead)}" }
+            for (i , warning) in negotiated.warnings.iter().enumerate() {
+                div { key: "{i}", class: "negotiated-warning", "⚠ {warning}" }
+            }
+        }
+    }
+}
+
+fn aead_status(aead: Option<preferences::AeadMode>) -> String {
+    match aead {
+        Some(mode) => format!("AEAD: every recipient accepts {}, so SEIPD v2 is used", mode),
+        None => "AEAD: not every recipient supports SEIPD v2, so SEIPD v1 with MDC is used"
+            .to_string(),
+    }
+}
+
+#[component]
+fn VerifyMessageTab() -> Element {
+    let mut public_key = use_signal(String::new);
+    let mut signed_message = use_signal(String::new);
+    let mut verification_result = use_signal(String::new);
+    let mut verified_literal = use_signal(|| None::<LiteralInfo>);
+    let mut error = use_signal(|| None::<Error>);
+    use_incoming_text(ActiveTab::Verify, Field::PublicKey, public_key);
+    use_incoming_text(ActiveTab::Verify, Field::SignedMessage, signed_message);
+
+    let verify_message = move |_| {
+        let public_key = public_key.read().clone();
+        let signed_message = signed_message.read().clone();
+
+        spawn(async move {
+            error.set(None);
+            verified_literal.set(None);
+            // An armored PGP MESSAGE is an inline signed one, anything else
+            // is read as a cleartext signed message.
+            let result = if detect::armored_content(&signed_message) == Some(Content::Message) {
+                crypto::verify_inline(&public_key, &signed_message).await.map(|(literal, data)| {
+                    verified_literal.set(Some(literal));
+                    String::from_utf8_lossy(&data).into_owned()
+                })
+            } else {
+                crypto::verify_cleartext(&public_key, &signed_message).await
+            };
+            match result {
+                Ok(message) => {
+                    verification_result
+                        .set(format!("✓ Signature Valid\n\nVerified Message:\n{}", message));
+                    show_message(
+                        "Signature verified successfully!".to_string(),
+                        Some(NotificationType::Success),
+                    );
+                }
+                Err(e) => {
+                    verification_result.set(match &e {
+                        Error::BadSignature(_) => "✗ Invalid Signature".to_string(),
+                        _ => format!("Error: {}", e),
+                    });
+                    show_error(error, e);
+                }
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "tab-panel",
+            h2 { "Verify Message" }
+
+            div { class: "form-group",
                 label { "Signer's Public Key:" }
                 textarea {
                     class: "key-textarea",
                     value: public_key.read().clone(),
-                    oninput: move |evt| public_key.set(evt.value().clone()),
+                    oninput: move |evt| public_key.set(evt.value()),
                     rows: 8,
                     cols: 50,
                     placeholder: "Paste the signer's public key here...",
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(public_key),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::PublicKey }
+                QrImport {
+                    on_import: move |content| {
+                        if let Some(key) = public_key_from_qr(content) {
+                            public_key.set(key);
+                        }
+                    },
+                }
+                KeyInspector { public_key: public_key.read().clone() }
             }
 
             div { class: "form-group",
@@ -582,11 +1104,19 @@ fn VerifyMessageTab() -> Element {
                 textarea {
                     class: "message-textarea",
                     value: signed_message.read().clone(),
-                    oninput: move |evt| signed_message.set(evt.value().clone()),
+                    oninput: move |evt| signed_message.set(evt.value()),
                     rows: 10,
                     cols: 50,
-                    placeholder: "Paste the entire signed message here (including headers and signature)...",
+                    placeholder: "Paste the entire cleartext or inline signed message here (including headers and signature)...",
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(signed_message),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::SignedMessage }
             }
 
             div { class: "form-group",
@@ -602,6 +1132,16 @@ fn VerifyMessageTab() -> Element {
                     rows: 8,
                     cols: 50,
                 }
+                if let Some(literal) = verified_literal.read().clone() {
+                    LiteralDetails { literal }
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&verification_result.read(), false),
+                        "Copy"
+                    }
+                }
             }
         }
     }
@@ -609,56 +1149,41 @@ fn VerifyMessageTab() -> Element {
 
 #[component]
 fn SignMessageTab() -> Element {
-    let mut private_key = use_signal(String::new);
+    let mut private_key = use_secret();
     let mut message_to_sign = use_signal(String::new);
     let signed_message = use_signal(String::new);
+    let mut inline = use_signal(|| false);
+    let literal_options = use_signal(LiteralOptions::default);
+    let custom_date = use_signal(|| None::<String>);
+    let compression = use_signal(|| None::<Compression>);
+    let mut error = use_signal(|| None::<Error>);
 
     let sign_message = move |_| {
         to_owned![private_key, message_to_sign, signed_message];
         async move {
-            let message_data = message_to_sign.read().clone().as_bytes().to_vec();
-            let skey = match utils::read_skey_from_string(private_key.read().clone()).await {
-                Ok(s) => s,
-                Err(e) => {
-                    show_message(
-                        format!("Error reading private key: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
-                }
-            };
-
-            let signed_data = match sign(skey, "", message_data).await {
-                Ok(s) => s,
-                Err(e) => {
-                    show_message(
-                        format!("Error signing message: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
-                }
+            error.set(None);
+            let skey = private_key.read().clone();
+            let message = message_to_sign.read().clone();
+            let result = if *inline.read() {
+                let literal = match literal_options_with_date(literal_options, custom_date) {
+                    Ok(literal) => literal,
+                    Err(e) => {
+                        show_error(error, e);
+                        return;
+                    }
+                };
+                let compression = compression.read().unwrap_or(Compression::Zlib);
+                crypto::sign_inline(&skey, "", message.as_bytes(), &literal, compression).await
+            } else {
+                crypto::sign_cleartext(&skey, "", &message).await
             };
-
-            // Format the signed message properly with the message and signature
-            let message_content = message_to_sign.read().clone();
-            let signature_part = match String::from_utf8(signed_data) {
-                Ok(s) => s,
-                Err(e) => {
-                    show_message(
-                        format!("Error converting signed message to string: {}", e),
-                        Some(NotificationType::Error),
-                    );
-                    return;
+            match result {
+                Ok(s) => {
+                    signed_message.set(s);
+                    private_key.set(Zeroizing::default());
                 }
-            };
-
-            // Create the complete PGP signed message format
-            let complete_signed_message = format!(
-                "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\n{}\n{}",
-                message_content, signature_part
-            );
-
-            signed_message.set(complete_signed_message);
+                Err(e) => show_error(error, e),
+            }
         }
     };
 
@@ -670,12 +1195,20 @@ fn SignMessageTab() -> Element {
                 label { "Your Private Key:" }
                 textarea {
                     class: "key-textarea",
-                    value: private_key.read().clone(),
-                    oninput: move |evt| private_key.set(evt.value().clone()),
+                    value: private_key.read().as_str(),
+                    oninput: move |evt| private_key.set(Zeroizing::new(evt.value())),
                     rows: 8,
                     cols: 50,
                     placeholder: "Paste your private key here...",
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(private_key),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::SecretKey }
             }
 
             div { class: "form-group",
@@ -683,11 +1216,36 @@ fn SignMessageTab() -> Element {
                 textarea {
                     class: "message-textarea",
                     value: message_to_sign.read().clone(),
-                    oninput: move |evt| message_to_sign.set(evt.value().clone()),
+                    oninput: move |evt| message_to_sign.set(evt.value()),
                     rows: 5,
                     cols: 50,
                     placeholder: "Type your message here...",
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(message_to_sign),
+                        "Paste"
+                    }
+                }
+            }
+
+            div { class: "form-group",
+                label { "Signature:" }
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| inline.set(evt.value() == "inline"),
+                    option { value: "cleartext", selected: !inline(), "Cleartext (readable text)" }
+                    option { value: "inline", selected: inline(), "Inline (PGP MESSAGE, like gpg --sign)" }
+                }
+                if *inline.read() {
+                    details { class: "literal-settings",
+                        summary { "File name, date, format and compression" }
+                        LiteralSettings { options: literal_options, custom_date }
+                        FieldError { error, field: Field::Date }
+                        CompressionSelect { compression, default_label: "Default (ZLIB)" }
+                    }
+                }
             }
 
             div { class: "form-group",
@@ -703,25 +1261,1449 @@ fn SignMessageTab() -> Element {
                     rows: 8,
                     cols: 50,
                 }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&signed_message.read(), false),
+                        "Copy"
+                    }
+                }
             }
         }
     }
 }
 
-// utils:
-fn show_message(message: String, message_type: Option<NotificationType>) {
-    let mut notifications = NOTIFICATIONS.write();
-    let mut id = NEXT_ID.write();
-    let notification_type = match message_type {
-        Some(t) => t,
-        None => NotificationType::Info,
+#[component]
+fn EncryptionModeSelect(use_passphrase: Signal<bool>) -> Element {
+    rsx! {
+        div { class: "form-group",
+            label { "Mode:" }
+            select {
+                class: "qr-select",
+                onchange: move |evt| use_passphrase.set(evt.value() == "passphrase"),
+                option { value: "key", selected: !use_passphrase(), "Public key" }
+                option { value: "passphrase", selected: use_passphrase(), "Passphrase only" }
+            }
+        }
+    }
+}
+
+#[component]
+fn S2kSettings(options: Signal<symmetric::S2kOptions>) -> Element {
+    let current = *options.read();
+
+    rsx! {
+        div { class: "form-group share-settings",
+            label {
+                "Cipher:"
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| {
+                        options.write().cipher = match evt.value().as_str() {
+                            "aes128" => SymmetricKeyAlgorithm::AES128,
+                            "aes192" => SymmetricKeyAlgorithm::AES192,
+                            _ => SymmetricKeyAlgorithm::AES256,
+                        };
+                    },
+                    option { value: "aes256", selected: current.cipher == SymmetricKeyAlgorithm::AES256, "AES-256" }
+                    option { value: "aes192", selected: current.cipher == SymmetricKeyAlgorithm::AES192, "AES-192" }
+                    option { value: "aes128", selected: current.cipher == SymmetricKeyAlgorithm::AES128, "AES-128" }
+                }
+            }
+            // Argon2 has fixed parameters, see the note below.
+            if current.kind == symmetric::S2kKind::Iterated {
+                label {
+                    "S2K hash:"
+                    select {
+                        class: "qr-select",
+                        onchange: move |evt| {
+                            options.write().hash = match evt.value().as_str() {
+                                "sha512" => HashAlgorithm::SHA2_512,
+                                _ => HashAlgorithm::SHA2_256,
+                            };
+                        },
+                        option { value: "sha256", selected: current.hash == HashAlgorithm::SHA2_256, "SHA-256" }
+                        option { value: "sha512", selected: current.hash == HashAlgorithm::SHA2_512, "SHA-512" }
+                    }
+                }
+                label {
+                    "S2K iterations:"
+                    select {
+                        class: "qr-select",
+                        onchange: move |evt| options.write().count = evt.value().parse().unwrap_or(224),
+                        for count in [96u8, 192, 224, 255] {
+                            option {
+                                key: "{count}",
+                                value: "{count}",
+                                selected: current.count == count,
+                                "{symmetric::decoded_count(count)} bytes"
+                            }
+                        }
+                    }
+                }
+            }
+            label {
+                "S2K type:"
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| options.write().kind = evt.value().parse().unwrap_or_default(),
+                    for kind in symmetric::S2kKind::ALL {
+                        option {
+                            key: "{kind}",
+                            value: "{kind}",
+                            selected: current.kind == kind,
+                            "{s2k_kind_name(kind)}"
+                        }
+                    }
+                }
+            }
+        }
+        if current.kind == symmetric::S2kKind::Argon2 {
+            p { class: "s2k-note",
+                "Argon2 makes guessing the passphrase costly by using 64 MiB of memory for each "
+                "try. It is part of RFC 9580, so the message is written as SEIPD v2 with OCB, "
+                "which GnuPG cannot read."
+            }
+        } else {
+            p { class: "s2k-note",
+                "Iterated and salted S2K can be tried quickly on graphics cards, so choose a long "
+                "passphrase, or Argon2 when the recipient uses an RFC 9580 implementation."
+            }
+        }
+    }
+}
+
+/// File name, date and format stored in the literal data packet. Leaving
+/// them empty keeps them from the recipient. A custom date is kept as typed
+/// in `custom_date` and checked when encrypting.
+#[component]
+fn LiteralSettings(
+    options: Signal<LiteralOptions>,
+    custom_date: Signal<Option<String>>,
+) -> Element {
+    let current = options.read().clone();
+
+    rsx! {
+        div { class: "form-group share-settings",
+            label {
+                "File name:"
+                input {
+                    class: "fingerprint-input",
+                    r#type: "text",
+                    maxlength: 255,
+                    value: current.file_name,
+                    oninput: move |evt| options.write().file_name = evt.value(),
+                    placeholder: "none",
+                }
+                button {
+                    class: "clipboard-button",
+                    onclick: move |_| paste_with(move |text| options.write().file_name = text),
+                    "Paste"
+                }
+            }
+            label {
+                "Format:"
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| options.write().mode = evt.value().parse().unwrap_or_default(),
+                    for mode in DataMode::ALL {
+                        option {
+                            key: "{mode}",
+                            value: "{mode}",
+                            selected: current.mode == mode,
+                            "{data_mode_name(mode)}"
+                        }
+                    }
+                }
+            }
+            label {
+                "Date:"
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| {
+                        custom_date.set((evt.value() == "custom").then(String::new));
+                        options.write().date = match evt.value().as_str() {
+                            "none" => Some(0),
+                            _ => None,
+                        };
+                    },
+                    option {
+                        value: "now",
+                        selected: current.date.is_none() && custom_date.read().is_none(),
+                        "Current time"
+                    }
+                    option { value: "none", selected: current.date == Some(0), "No date" }
+                    option {
+                        value: "custom",
+                        selected: custom_date.read().is_some(),
+                        "Custom (UTC)"
+                    }
+                }
+            }
+            if let Some(date) = custom_date() {
+                // A plain text field: a datetime-local picker would give local
+                // time, while the date is stored and read as UTC.
+                input {
+                    class: "fingerprint-input",
+                    r#type: "text",
+                    placeholder: "YYYY-MM-DD HH:MM (UTC)",
+                    value: date,
+                    onchange: move |evt| custom_date.set(Some(evt.value())),
+                }
+            }
+        }
+    }
+}
+
+/// The literal options with the custom date, if any, checked and filled in.
+fn literal_options_with_date(
+    options: Signal<LiteralOptions>,
+    custom_date: Signal<Option<String>>,
+) -> Result<LiteralOptions, Error> {
+    let mut literal = options.read().clone();
+    if let Some(text) = &*custom_date.read() {
+        let date = packets::parse_time(text).ok_or_else(|| Error::InvalidDate(text.clone()))?;
+        literal.date = Some(date);
+    }
+    Ok(literal)
+}
+
+/// Compression picker; `None` stands for the default described by
+/// `default_label`.
+#[component]
+fn CompressionSelect(
+    compression: Signal<Option<Compression>>,
+    default_label: &'static str,
+) -> Element {
+    rsx! {
+        div { class: "form-group share-settings",
+            label {
+                "Compression:"
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| compression.set(evt.value().parse().ok()),
+                    option { value: "default", selected: compression.read().is_none(), "{default_label}" }
+                    for algorithm in Compression::ALL {
+                        option {
+                            key: "{algorithm}",
+                            value: "{algorithm}",
+                            selected: *compression.read() == Some(algorithm),
+                            "{compression_name(algorithm)}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn compression_name(compression: Compression) -> &'static str {
+    match compression {
+        Compression::None => "None",
+        Compression::Zip => "ZIP",
+        Compression::Zlib => "ZLIB",
+        Compression::Bzip2 => "BZip2",
+    }
+}
+
+fn s2k_kind_name(kind: symmetric::S2kKind) -> &'static str {
+    match kind {
+        symmetric::S2kKind::Iterated => "Iterated and salted",
+        symmetric::S2kKind::Argon2 => "Argon2 (RFC 9580)",
+    }
+}
+
+fn profile_name(profile: KeyProfile) -> &'static str {
+    match profile {
+        KeyProfile::Rfc4880 => "RFC 4880 compatible",
+        KeyProfile::Rfc9580 => "RFC 9580",
+    }
+}
+
+fn data_mode_name(mode: DataMode) -> &'static str {
+    match mode {
+        DataMode::Binary => "Binary",
+        DataMode::Text => "Text",
+        DataMode::Utf8 => "UTF-8 text",
+    }
+}
+
+#[component]
+fn PaperBackupTab() -> Element {
+    let mut secret_key = use_secret();
+    let mut paper_backup = use_signal(|| None::<crypto::PaperBackup>);
+    let mut restore_public_key = use_signal(String::new);
+    let mut restore_backup = use_secret();
+    let restored_key = use_secret();
+    let mut error = use_signal(|| None::<Error>);
+
+    let create_backup = move |_| {
+        to_owned![secret_key, paper_backup];
+        async move {
+            error.set(None);
+            let skey = secret_key.read().clone();
+            match crypto::create_paper_backup(&skey).await {
+                Ok(backup) => {
+                    paper_backup.set(Some(backup));
+                    secret_key.set(Zeroizing::default());
+                }
+                Err(e) => show_error(error, e),
+            }
+        }
     };
 
-    notifications.push(Notification {
-        message,
-        notification_type,
-        id: *id,
-    });
+    let save_backup = move |_| async move {
+        let Some(html) = paper_backup.read().as_ref().map(|b| b.to_html()) else {
+            return;
+        };
+        match save_file("paper_backup.html", "text/html", html.as_bytes()).await {
+            Ok(true) => show_message(
+                "Paper backup saved, print it and delete the file".to_string(),
+                Some(NotificationType::Success),
+            ),
+            Ok(false) => {}
+            Err(e) => show_message(
+                format!("Error saving paper backup: {}", e),
+                Some(NotificationType::Error),
+            ),
+        }
+    };
 
-    *id += 1;
+    let restore_key = move |_| {
+        to_owned![restore_public_key, restore_backup, restored_key];
+        async move {
+            error.set(None);
+            let pkey = restore_public_key.read().clone();
+            let backup = restore_backup.read().clone();
+            match crypto::restore_paper_backup(&pkey, &backup).await {
+                Ok(armored) => {
+                    restored_key.set(armored);
+                    restore_backup.set(Zeroizing::default());
+                    show_message(
+                        "Secret key restored successfully!".to_string(),
+                        Some(NotificationType::Success),
+                    );
+                }
+                Err(e) => show_error(error, e),
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "tab-panel",
+            h2 { "Paper Backup" }
+
+            div { class: "form-group",
+                label { "Your Private Key:" }
+                textarea {
+                    class: "key-textarea",
+                    value: secret_key.read().as_str(),
+                    oninput: move |evt| secret_key.set(Zeroizing::new(evt.value())),
+                    rows: 8,
+                    cols: 50,
+                    placeholder: "Paste your private key here...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(secret_key),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::SecretKey }
+            }
+
+            div { class: "form-group",
+                button { class: "generate-button", onclick: create_backup, "Create Paper Backup" }
+            }
+
+            if let Some(backup) = &*paper_backup.read() {
+                div { class: "form-group",
+                    label { "Paper Backup:" }
+                    textarea {
+                        class: "key-textarea",
+                        readonly: true,
+                        value: backup.text.as_str(),
+                        rows: 10,
+                        cols: 50,
+                    }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| {
+                                if let Some(backup) = &*paper_backup.read() {
+                                    copy_to_clipboard(&backup.text, true);
+                                }
+                            },
+                            "Copy"
+                        }
+                    }
+                }
+                div { class: "form-group qr-chunks",
+                    for (i , chunk) in backup.qr_chunks.iter().enumerate() {
+                        div {
+                            key: "{i}",
+                            class: "qr-code",
+                            dangerous_inner_html: qr::to_svg(chunk).unwrap_or_default(),
+                        }
+                    }
+                }
+                div { class: "form-group",
+                    button { class: "generate-button", onclick: save_backup, "Save as HTML" }
+                }
+            }
+
+            h2 { "Restore From Paper Backup" }
+
+            div { class: "form-group",
+                label { "Public Key:" }
+                textarea {
+                    class: "key-textarea",
+                    value: restore_public_key.read().clone(),
+                    oninput: move |evt| restore_public_key.set(evt.value()),
+                    rows: 8,
+                    cols: 50,
+                    placeholder: "Paste the public key of the backed up key here...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(restore_public_key),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::PublicKey }
+            }
+
+            div { class: "form-group",
+                label { "Paper Backup:" }
+                textarea {
+                    class: "message-textarea",
+                    value: restore_backup.read().as_str(),
+                    oninput: move |evt| restore_backup.set(Zeroizing::new(evt.value())),
+                    rows: 8,
+                    cols: 50,
+                    placeholder: "Type the paper backup lines or paste the scanned QR chunks here...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(restore_backup),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::PaperBackup }
+            }
+
+            div { class: "form-group",
+                button { class: "generate-button", onclick: restore_key, "Restore Secret Key" }
+            }
+
+            div { class: "form-group",
+                label { "Restored Private Key:" }
+                textarea {
+                    class: "key-textarea",
+                    readonly: true,
+                    value: restored_key.read().as_str(),
+                    rows: 8,
+                    cols: 50,
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&restored_key.read(), true),
+                        "Copy"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SecretSharingTab() -> Element {
+    let mut secret_kind = use_signal(|| shamir::SecretKind::SecretKey);
+    let mut secret = use_secret();
+    let mut threshold = use_signal(|| 2u8);
+    let mut share_count = use_signal(|| 3u8);
+    let shares = use_signal(|| Zeroizing::new(Vec::<String>::new()));
+    let mut shares_input = use_secret();
+    let recovered_secret = use_secret();
+    let mut error = use_signal(|| None::<Error>);
+
+    let split_secret = move |_| {
+        to_owned![secret_kind, secret, threshold, share_count, shares];
+        async move {
+            error.set(None);
+            let data = secret.read().clone();
+            let kind = *secret_kind.read();
+            match crypto::split_secret(&data, kind, *threshold.read(), *share_count.read()).await {
+                Ok(s) => {
+                    shares.set(s);
+                    secret.set(Zeroizing::default());
+                }
+                Err(e) => show_error(error, e),
+            }
+        }
+    };
+
+    let combine_shares = move |_| {
+        to_owned![shares_input, recovered_secret];
+        async move {
+            error.set(None);
+            let input = shares_input.read().clone();
+            match crypto::combine_shares(&input).await {
+                Ok((_, secret)) => {
+                    recovered_secret.set(secret);
+                    shares_input.set(Zeroizing::default());
+                    show_message(
+                        "Secret recovered successfully!".to_string(),
+                        Some(NotificationType::Success),
+                    );
+                }
+                Err(e) => show_error(error, e),
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "tab-panel",
+            h2 { "Split Secret" }
+
+            div { class: "form-group",
+                label { "Secret To Split:" }
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| {
+                        secret_kind
+                            .set(
+                                if evt.value() == "passphrase" {
+                                    shamir::SecretKind::Passphrase
+                                } else {
+                                    shamir::SecretKind::SecretKey
+                                },
+                            )
+                    },
+                    option { value: "secret-key", "Private key" }
+                    option { value: "passphrase", "Passphrase" }
+                }
+            }
+
+            div { class: "form-group",
+                textarea {
+                    class: "key-textarea",
+                    value: secret.read().as_str(),
+                    oninput: move |evt| secret.set(Zeroizing::new(evt.value())),
+                    rows: 8,
+                    cols: 50,
+                    placeholder: "Paste your private key or passphrase here...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(secret),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::SecretKey }
+            }
+
+            div { class: "form-group share-settings",
+                label {
+                    "Shares required:"
+                    input {
+                        class: "number-input",
+                        r#type: "number",
+                        min: 2,
+                        max: 255,
+                        value: "{threshold}",
+                        oninput: move |evt| threshold.set(evt.value().parse().unwrap_or(2)),
+                    }
+                }
+                label {
+                    "Total shares:"
+                    input {
+                        class: "number-input",
+                        r#type: "number",
+                        min: 2,
+                        max: 255,
+                        value: "{share_count}",
+                        oninput: move |evt| share_count.set(evt.value().parse().unwrap_or(3)),
+                    }
+                }
+            }
+
+            div { class: "form-group",
+                button { class: "generate-button", onclick: split_secret, "Split Secret" }
+            }
+
+            for (i , share) in shares.read().iter().enumerate() {
+                div { key: "{i}", class: "form-group",
+                    label { "Share {i + 1}:" }
+                    textarea {
+                        class: "key-textarea",
+                        readonly: true,
+                        value: share.as_str(),
+                        rows: 8,
+                        cols: 50,
+                    }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| copy_to_clipboard(&shares.read()[i], true),
+                            "Copy"
+                        }
+                    }
+                }
+            }
+
+            h2 { "Recombine Shares" }
+
+            div { class: "form-group",
+                label { "Shares:" }
+                textarea {
+                    class: "key-textarea",
+                    value: shares_input.read().as_str(),
+                    oninput: move |evt| shares_input.set(Zeroizing::new(evt.value())),
+                    rows: 10,
+                    cols: 50,
+                    placeholder: "Paste the shares here, one after another...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(shares_input),
+                        "Paste"
+                    }
+                }
+                FieldError { error, field: Field::Shares }
+            }
+
+            div { class: "form-group",
+                button { class: "generate-button", onclick: combine_shares, "Recombine Shares" }
+            }
+
+            div { class: "form-group",
+                label { "Recovered Secret:" }
+                textarea {
+                    class: "key-textarea",
+                    readonly: true,
+                    value: recovered_secret.read().as_str(),
+                    rows: 8,
+                    cols: 50,
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&recovered_secret.read(), true),
+                        "Copy"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn InspectTab() -> Element {
+    let mut input = use_secret();
+    let listing = use_signal(String::new);
+    let mut error = use_signal(|| None::<Error>);
+    use_incoming_text(ActiveTab::Inspect, Field::PacketData, input);
+
+    let inspect_file = move |evt: FormEvent| async move {
+        let Some(file_engine) = evt.files() else {
+            return;
+        };
+        for file_name in file_engine.files() {
+            match file_engine.read_file(&file_name).await {
+                Some(bytes) => list_packets(&bytes, listing, error),
+                None => show_message(
+                    format!("Error reading file {}", file_name),
+                    Some(NotificationType::Error),
+                ),
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "tab-panel",
+            h2 { "Inspect Packets" }
+            p { "Secret key material and message contents are never shown, only their sizes." }
+
+            div { class: "form-group",
+                label { "OpenPGP Data:" }
+                textarea {
+                    class: "message-textarea",
+                    value: input.read().as_str(),
+                    oninput: move |evt| input.set(Zeroizing::new(evt.value())),
+                    rows: 8,
+                    cols: 50,
+                    placeholder: "Paste a key, message or signature here...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(input),
+                        "Paste"
+                    }
+                }
+                label { class: "qr-import",
+                    "📂 Inspect a binary or armored file"
+                    input { r#type: "file", onchange: inspect_file }
+                }
+                FieldError { error, field: Field::PacketData }
+                KeyHealth { key: input.read().clone() }
+            }
+
+            div { class: "form-group",
+                button {
+                    class: "verify-button",
+                    onclick: move |_| list_packets(input.read().as_bytes(), listing, error),
+                    "List Packets"
+                }
+            }
+
+            div { class: "form-group",
+                label { "Packets:" }
+                textarea {
+                    class: "verification-textarea packet-listing",
+                    readonly: true,
+                    value: listing.read().clone(),
+                    rows: 16,
+                    cols: 50,
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&listing.read(), false),
+                        "Copy"
+                    }
+                }
+            }
+
+            MergeKeys {}
+        }
+    }
+}
+
+/// Merges copies of one public key from several sources and hands the
+/// result to the Encrypt or Verify tab.
+#[component]
+fn MergeKeys() -> Element {
+    let mut keys = use_signal(String::new);
+    let mut merged = use_signal(|| None::<crypto::MergedKey>);
+    let mut error = use_signal(|| None::<Error>);
+
+    let merge_keys = move |_| async move {
+        error.set(None);
+        let armored = keys.read().clone();
+        match crypto::merge_keys(&armored).await {
+            Ok(key) => merged.set(Some(key)),
+            Err(e) => {
+                merged.set(None);
+                show_error(error, e);
+            }
+        }
+    };
+
+    let public_key = move || {
+        merged
+            .read()
+            .as_ref()
+            .map(|key| key.public_key.clone())
+            .unwrap_or_default()
+    };
+
+    rsx! {
+        h2 { "Merge Keys" }
+        p {
+            "Combine copies of the same public key, e.g. from a keyserver and from WKD, "
+            "into one key with every user ID, subkey and signature."
+        }
+
+        div { class: "form-group",
+            label { "Public Keys:" }
+            textarea {
+                class: "key-textarea",
+                value: keys.read().clone(),
+                oninput: move |evt| keys.set(evt.value()),
+                rows: 8,
+                cols: 50,
+                placeholder: "Paste two or more copies of a public key here...",
+            }
+            div { class: "clipboard-actions",
+                button {
+                    class: "clipboard-button",
+                    onclick: move |_| paste_from_clipboard(keys),
+                    "Paste"
+                }
+            }
+            FieldError { error, field: Field::PublicKey }
+        }
+
+        div { class: "form-group",
+            button { class: "verify-button", onclick: merge_keys, "Merge Keys" }
+        }
+
+        if let Some(key) = &*merged.read() {
+            div { class: "form-group",
+                label { "Changes:" }
+                pre { class: "merge-report", "{key.report.to_text()}" }
+            }
+        }
+
+        div { class: "form-group",
+            label { "Merged Public Key:" }
+            textarea {
+                class: "key-textarea",
+                readonly: true,
+                value: public_key(),
+                rows: 8,
+                cols: 50,
+            }
+            div { class: "clipboard-actions",
+                button {
+                    class: "clipboard-button",
+                    onclick: move |_| copy_to_clipboard(&public_key(), false),
+                    "Copy"
+                }
+                button {
+                    class: "clipboard-button",
+                    disabled: merged.read().is_none(),
+                    onclick: move |_| open_text(ActiveTab::Encrypt, Field::PublicKey, public_key()),
+                    "Use in Encrypt"
+                }
+                button {
+                    class: "clipboard-button",
+                    disabled: merged.read().is_none(),
+                    onclick: move |_| open_text(ActiveTab::Verify, Field::PublicKey, public_key()),
+                    "Use in Verify"
+                }
+            }
+        }
+    }
+}
+
+/// Fills `listing` with the packet tree of `data`.
+fn list_packets(data: &[u8], mut listing: Signal<String>, mut error: Signal<Option<Error>>) {
+    error.set(None);
+    match crypto::list_packets(data) {
+        Ok(packets) => listing.set(packets::to_text(&packets)),
+        Err(e) => {
+            listing.set(String::new());
+            show_error(error, e);
+        }
+    }
+}
+
+#[component]
+fn ArmorTab() -> Element {
+    let mut input = use_secret();
+    let mut dearmored = use_signal(|| None::<crypto::Dearmored>);
+    let mut headers = use_signal(String::new);
+    let mut output = use_secret();
+    let mut error = use_signal(|| None::<Error>);
+
+    let mut dearmor = move |text: &str| {
+        error.set(None);
+        output.set(Zeroizing::new(String::new()));
+        match crypto::dearmor(text) {
+            Ok(block) => {
+                headers.set(
+                    block
+                        .headers
+                        .iter()
+                        .map(|(key, value)| format!("{}: {}\n", key, value))
+                        .collect(),
+                );
+                dearmored.set(Some(block));
+            }
+            Err(e) => {
+                dearmored.set(None);
+                show_error(error, e);
+            }
+        }
+    };
+
+    let open_file = move |evt: FormEvent| async move {
+        let Some(file_engine) = evt.files() else {
+            return;
+        };
+        let Some(file_name) = file_engine.files().into_iter().next() else {
+            return;
+        };
+        let Some(bytes) = file_engine.read_file(&file_name).await else {
+            show_message(
+                format!("Error reading file {}", file_name),
+                Some(NotificationType::Error),
+            );
+            return;
+        };
+        // Binary files are armored first, so both kinds end up in the
+        // text field and can be edited the same way.
+        let text = match String::from_utf8(bytes) {
+            Ok(text) => Ok(text),
+            Err(e) => crypto::enarmor(e.as_bytes(), None, ""),
+        };
+        match text {
+            Ok(text) => {
+                dearmor(&text);
+                input.set(Zeroizing::new(text));
+            }
+            Err(e) => show_error(error, e),
+        }
+    };
+
+    let armor_data = move |_| {
+        error.set(None);
+        let result = match &*dearmored.read() {
+            Some(block) => crypto::enarmor(&block.data, Some(block.label.as_str()), &headers.read()),
+            None => Err(Error::MissingInput(Field::PacketData)),
+        };
+        match result {
+            Ok(armored) => output.set(Zeroizing::new(armored)),
+            Err(e) => show_error(error, e),
+        }
+    };
+
+    let save_binary = move |_| async move {
+        let Some(data) = dearmored.read().as_ref().map(|block| block.data.clone()) else {
+            return;
+        };
+        match save_file("dearmored.gpg", "application/octet-stream", &data).await {
+            Ok(true) => show_message(
+                "Binary data saved".to_string(),
+                Some(NotificationType::Success),
+            ),
+            Ok(false) => {}
+            Err(e) => show_message(
+                format!("Error saving file: {}", e),
+                Some(NotificationType::Error),
+            ),
+        }
+    };
+
+    rsx! {
+        div { class: "tab-panel",
+            h2 { "ASCII Armor" }
+            p {
+                "Convert between binary and armored OpenPGP data, check the checksum and edit the headers. "
+                "Quoted, indented or wrapped text from emails is repaired."
+            }
+
+            div { class: "form-group",
+                label { "Armored Data:" }
+                textarea {
+                    class: "message-textarea",
+                    value: input.read().as_str(),
+                    oninput: move |evt| input.set(Zeroizing::new(evt.value())),
+                    rows: 8,
+                    cols: 50,
+                    placeholder: "Paste armored data here, even if it was mangled by an email client...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(input),
+                        "Paste"
+                    }
+                }
+                label { class: "qr-import",
+                    "📂 Open a binary or armored file"
+                    input { r#type: "file", onchange: open_file }
+                }
+                FieldError { error, field: Field::PacketData }
+            }
+
+            div { class: "form-group",
+                button {
+                    class: "verify-button",
+                    onclick: move |_| {
+                        let text = input.read().clone();
+                        dearmor(&text);
+                    },
+                    "Dearmor"
+                }
+            }
+
+            if let Some(block) = &*dearmored.read() {
+                div { class: "form-group armor-report",
+                    div { "Type: {block.label}" }
+                    div { "Size: {block.data.len()} bytes" }
+                    div {
+                        class: if matches!(block.checksum, armor::Checksum::Mismatch { .. }) { "checksum-mismatch" } else { "" },
+                        "Checksum: {block.checksum}"
+                    }
+                    if block.repaired {
+                        div { "Repaired copy-paste damage in the input." }
+                    }
+                    button { class: "generate-button", onclick: save_binary, "Save Binary" }
+                }
+
+                div { class: "form-group",
+                    label { "Headers:" }
+                    textarea {
+                        class: "message-textarea",
+                        value: headers.read().clone(),
+                        oninput: move |evt| headers.set(evt.value()),
+                        rows: 3,
+                        cols: 50,
+                        placeholder: "Comment: for Bob",
+                    }
+                    div { class: "clipboard-actions",
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| paste_from_clipboard(headers),
+                            "Paste"
+                        }
+                    }
+                    button { class: "encrypt-button", onclick: armor_data, "Armor" }
+                }
+            }
+
+            div { class: "form-group",
+                label { "Armored Output:" }
+                textarea {
+                    class: "encrypted-textarea",
+                    readonly: true,
+                    value: output.read().as_str(),
+                    rows: 8,
+                    cols: 50,
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| copy_to_clipboard(&output.read(), true),
+                        "Copy"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn KeyInspector(public_key: ReadOnlySignal<String>) -> Element {
+    let mut compare_input = use_signal(String::new);
+    let mut qr_content = use_signal(|| QrContent::Fingerprint);
+    let key_info = use_resource(move || async move {
+        let key = public_key.read().clone();
+        crypto::inspect_public_key(&key).await.ok()
+    });
+
+    let (fpr, wkd_url) = match &*key_info.read() {
+        Some(Some(info)) => (info.fingerprint.clone(), info.wkd_url.clone()),
+        _ => return rsx! {},
+    };
+
+    let grouped_hex = fingerprint::to_grouped_hex(&fpr);
+    let words = fingerprint::to_words(&fpr).join(" ");
+    let qr_data = match *qr_content.read() {
+        QrContent::Fingerprint => fingerprint::openpgp4fpr_uri(&fpr),
+        QrContent::FingerprintWkd => match &wkd_url {
+            Some(url) => format!("{}\n{}", fingerprint::openpgp4fpr_uri(&fpr), url),
+            None => fingerprint::openpgp4fpr_uri(&fpr),
+        },
+        QrContent::PublicKey => public_key.read().trim().to_string(),
+    };
+    let qr_code = match qr::to_svg(&qr_data) {
+        Ok(svg) => svg,
+        Err(e) => format!("<p>Cannot render QR code: {}</p>", e),
+    };
+
+    let compare_value = compare_input.read().clone();
+    let (compare_class, compare_text) = if compare_value.trim().is_empty() {
+        ("fingerprint-compare", "")
+    } else {
+        match fingerprint::parse(&compare_value) {
+            Some(other) if other == fpr => ("fingerprint-compare match", "✓ Fingerprints match"),
+            Some(_) => (
+                "fingerprint-compare mismatch",
+                "✗ Fingerprints do NOT match",
+            ),
+            None => (
+                "fingerprint-compare mismatch",
+                "✗ Not a valid fingerprint",
+            ),
+        }
+    };
+
+    let save_qr = move |_| {
+        let qr_data = qr_data.clone();
+        async move {
+            let png = match qr::to_png(&qr_data) {
+                Ok(png) => png,
+                Err(e) => {
+                    show_message(
+                        format!("Error rendering QR code: {}", e),
+                        Some(NotificationType::Error),
+                    );
+                    return;
+                }
+            };
+            match save_file("public_key_qr.png", "image/png", &png).await {
+                Ok(true) => show_message(
+                    "QR code saved".to_string(),
+                    Some(NotificationType::Success),
+                ),
+                Ok(false) => {}
+                Err(e) => show_message(
+                    format!("Error saving QR code: {}", e),
+                    Some(NotificationType::Error),
+                ),
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "key-inspector",
+            div { class: "fingerprint-details",
+                label { "Fingerprint:" }
+                div { class: "fingerprint-hex", "{grouped_hex}" }
+                label { "Fingerprint Words:" }
+                div { class: "fingerprint-words", "{words}" }
+                if let Some(url) = &wkd_url {
+                    label { "WKD URL:" }
+                    div { class: "fingerprint-words", "{url}" }
+                }
+                label { "Compare Fingerprint:" }
+                input {
+                    class: "fingerprint-input",
+                    value: compare_value.clone(),
+                    oninput: move |evt| compare_input.set(evt.value()),
+                    placeholder: "Paste a fingerprint received out-of-band...",
+                }
+                div { class: "clipboard-actions",
+                    button {
+                        class: "clipboard-button",
+                        onclick: move |_| paste_from_clipboard(compare_input),
+                        "Paste"
+                    }
+                }
+                div { class: "{compare_class}", "{compare_text}" }
+            }
+            div { class: "qr-section",
+                div { class: "qr-code", dangerous_inner_html: "{qr_code}" }
+                select {
+                    class: "qr-select",
+                    onchange: move |evt| {
+                        qr_content
+                            .set(
+                                match evt.value().as_str() {
+                                    "wkd" => QrContent::FingerprintWkd,
+                                    "key" => QrContent::PublicKey,
+                                    _ => QrContent::Fingerprint,
+                                },
+                            )
+                    },
+                    option { value: "fingerprint", "Fingerprint" }
+                    option { value: "wkd", disabled: wkd_url.is_none(), "Fingerprint + WKD URL" }
+                    option { value: "key", "Full public key" }
+                }
+                button { class: "qr-button", onclick: save_qr, "Save QR as PNG" }
+            }
+            KeyHealth { key: Zeroizing::new(public_key.read().clone()) }
+        }
+    }
+}
+
+/// Problems found in a key, each with a button for the fix-it the tool
+/// offers.
+#[component]
+fn KeyHealth(key: ReadOnlySignal<Zeroizing<String>>) -> Element {
+    let Ok(findings) = crypto::audit_key(&key.read()) else {
+        return rsx! {};
+    };
+    let summary = match findings.len() {
+        0 => "no problems found".to_string(),
+        1 => "1 problem".to_string(),
+        n => format!("{} problems", n),
+    };
+
+    rsx! {
+        details { class: "key-health", open: findings.iter().any(|f| f.severity == Severity::Critical),
+            summary { "Key health: {summary}" }
+            for (i , finding) in findings.into_iter().enumerate() {
+                div { key: "{i}", class: "key-health-finding {finding.severity}",
+                    span { class: "key-health-severity", "{finding.severity}" }
+                    span { "{finding.message}" }
+                    if let Some(fix) = finding.fix {
+                        button {
+                            class: "clipboard-button",
+                            onclick: move |_| apply_fix(fix),
+                            "{fix}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn apply_fix(fix: FixIt) {
+    match fix {
+        FixIt::GenerateKey => *ACTIVETAB.write() = ActiveTab::Generate,
+    }
+}
+
+#[component]
+fn QrImport(on_import: EventHandler<String>) -> Element {
+    let import_qr = move |evt: FormEvent| async move {
+        let Some(file_engine) = evt.files() else {
+            return;
+        };
+        for file_name in file_engine.files() {
+            let Some(bytes) = file_engine.read_file(&file_name).await else {
+                show_message(
+                    format!("Error reading file {}", file_name),
+                    Some(NotificationType::Error),
+                );
+                continue;
+            };
+            match qr::decode_image(&bytes) {
+                Ok(content) => on_import.call(content),
+                Err(e) => show_message(
+                    format!("Error reading QR code: {}", e),
+                    Some(NotificationType::Error),
+                ),
+            }
+        }
+    };
+
+    rsx! {
+        label { class: "qr-import",
+            "📷 Import from QR image"
+            input { r#type: "file", accept: "image/*", onchange: import_qr }
+        }
+    }
+}
+
+#[component]
+fn FieldError(error: Signal<Option<Error>>, field: Field) -> Element {
+    match &*error.read() {
+        Some(e) if e.field() == Some(field) => rsx! {
+            div { class: "field-error",
+                div { class: "field-error-message", "{e}" }
+                div { class: "field-error-hint", "{e.suggestion()}" }
+            }
+        },
+        _ => rsx! {},
+    }
+}
+
+// utils:
+fn show_message(message: String, message_type: Option<NotificationType>) {
+    let mut notifications = NOTIFICATIONS.write();
+    let mut id = NEXT_ID.write();
+    let notification_type = match message_type {
+        Some(t) => t,
+        None => NotificationType::Info,
+    };
+
+    notifications.push(Notification {
+        message,
+        notification_type,
+        id: *id,
+    });
+
+    *id += 1;
+}
+
+/// Lets the user save `data` under `file_name`. Returns `false` when the
+/// save dialog was cancelled.
+#[cfg(feature = "desktop")]
+async fn save_file(file_name: &str, _mime: &str, data: &[u8]) -> Result<bool, String> {
+    let extension = file_name.rsplit('.').next().unwrap_or_default();
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name(file_name)
+        .add_filter(extension, &[extension])
+        .save_file()
+        .await
+    else {
+        return Ok(false);
+    };
+    file.write(data).await.map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Lets the user save `data` under `file_name` by starting a browser
+/// download, which never leaves the page.
+#[cfg(not(feature = "desktop"))]
+async fn save_file(file_name: &str, mime: &str, data: &[u8]) -> Result<bool, String> {
+    use base64::Engine;
+
+    let data = base64::engine::general_purpose::STANDARD.encode(data);
+    document::eval(&format!(
+        r#"const link = document.createElement("a");
+        link.href = "data:{};base64,{}";
+        link.download = {:?};
+        link.click();"#,
+        mime, data, file_name
+    ))
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Extracts a public key from decoded QR content. Fingerprint-only codes
+/// cannot be turned into a key, so the user is told where to fetch it.
+fn public_key_from_qr(content: String) -> Option<String> {
+    if content.contains("-----BEGIN PGP PUBLIC KEY BLOCK-----") {
+        return Some(content);
+    }
+    match content.lines().nth(1) {
+        Some(url) if content.starts_with("OPENPGP4FPR:") => show_message(
+            format!("QR code contains only a fingerprint. Fetch the key from {}", url),
+            Some(NotificationType::Info),
+        ),
+        _ if content.starts_with("OPENPGP4FPR:") => show_message(
+            "QR code contains only a fingerprint, not a public key".to_string(),
+            Some(NotificationType::Info),
+        ),
+        _ => show_message(
+            "QR code does not contain a public key".to_string(),
+            Some(NotificationType::Error),
+        ),
+    }
+    None
+}
+
+/// Copies `text` to the clipboard. Sensitive text is removed from it again
+/// after the timeout chosen in the header.
+fn copy_to_clipboard(text: &str, sensitive: bool) {
+    let text = Zeroizing::new(text.to_string());
+    let clear_after = match *CLIPBOARD_CLEAR_SECONDS.read() {
+        seconds if sensitive && seconds > 0 => Some(Duration::from_secs(seconds)),
+        _ => None,
+    };
+    spawn(async move {
+        match clipboard::copy(&text, clear_after).await {
+            Ok(()) => show_message(
+                match clear_after {
+                    Some(delay) => format!(
+                        "Copied to clipboard, it will be cleared in {} seconds",
+                        delay.as_secs()
+                    ),
+                    None => "Copied to clipboard".to_string(),
+                },
+                Some(NotificationType::Success),
+            ),
+            Err(e) => show_message(
+                format!("Error copying to clipboard: {}", e),
+                Some(NotificationType::Error),
+            ),
+        }
+    });
+}
+
+/// Replaces the contents of `target` with the clipboard text.
+fn paste_from_clipboard<T: From<String> + 'static>(mut target: Signal<T>) {
+    paste_with(move |text| target.set(text.into()));
+}
+
+/// Hands the clipboard text to `apply`, for fields that are part of a
+/// larger value.
+fn paste_with(apply: impl FnOnce(String) + 'static) {
+    spawn(async move {
+        match clipboard::paste().await {
+            Ok(text) => apply(text),
+            Err(e) => show_message(
+                format!("Error pasting from clipboard: {}", e),
+                Some(NotificationType::Error),
+            ),
+        }
+    });
+}
+
+/// Human readable name of detected content, `None` being plain text.
+fn content_description(content: Option<Content>) -> &'static str {
+    match content {
+        Some(Content::PublicKey) => "public key",
+        Some(Content::SecretKey) => "private key",
+        Some(Content::Message) => "encrypted message",
+        Some(Content::SignedMessage) => "cleartext signed message",
+        Some(Content::Signature) => "detached signature",
+        None => "plain text",
+    }
+}
+
+/// Tab and field that handle `content`, plain text being a message to
+/// encrypt. Detached signatures can only be inspected.
+fn destination(content: Option<Content>) -> (ActiveTab, Field) {
+    match content {
+        Some(Content::PublicKey) => (ActiveTab::Encrypt, Field::PublicKey),
+        Some(Content::SecretKey) => (ActiveTab::Decrypt, Field::SecretKey),
+        Some(Content::Message) => (ActiveTab::Decrypt, Field::EncryptedMessage),
+        Some(Content::SignedMessage) => (ActiveTab::Verify, Field::SignedMessage),
+        Some(Content::Signature) => (ActiveTab::Inspect, Field::PacketData),
+        None => (ActiveTab::Encrypt, Field::Message),
+    }
+}
+
+/// Switches to the tab that handles `data` and hands it over, armored, to
+/// the matching field.
+fn open_data(data: &[u8]) {
+    let (content, text) = match detect::detect(data) {
+        Some((content, text)) => (Some(content), text),
+        None => match std::str::from_utf8(data) {
+            Ok(text) => (None, text.to_string()),
+            Err(_) => {
+                show_message(
+                    "File does not contain OpenPGP data".to_string(),
+                    Some(NotificationType::Error),
+                );
+                return;
+            }
+        },
+    };
+    let (tab, field) = destination(content);
+    open_text(tab, field, text);
+}
+
+/// Switches to `tab` and hands `text` over to its `field`.
+fn open_text(tab: ActiveTab, field: Field, text: String) {
+    INCOMING_TEXT.write().push(IncomingText {
+        tab,
+        field,
+        text: Zeroizing::new(text),
+    });
+    *ACTIVETAB.write() = tab;
+}
+
+/// Fills `target` with text handed over to `field` of `tab` through
+/// [`open_text`] once that tab is shown. Several tabs have a public key
+/// field, so text is matched by both.
+fn use_incoming_text<T: From<String> + 'static>(
+    tab: ActiveTab,
+    field: Field,
+    mut target: Signal<T>,
+) {
+    use_effect(move || {
+        let position = INCOMING_TEXT
+            .read()
+            .iter()
+            .position(|i| i.tab == tab && i.field == field);
+        if let Some(position) = position {
+            let mut incoming = INCOMING_TEXT.write().remove(position);
+            target.set(std::mem::take(&mut *incoming.text).into());
+        }
+    });
+}
+
+/// Signal for secret text: private keys, passphrases and plaintext. The
+/// buffer is wiped whenever it is replaced, and when the tab owning it is
+/// left, since switching tabs drops the tab's signals.
+fn use_secret() -> Signal<Zeroizing<String>> {
+    use_signal(Zeroizing::default)
+}
+
+/// Notifies the user about a failed operation and remembers the error so
+/// the affected field can show a hint.
+fn show_error(mut error: Signal<Option<Error>>, e: Error) {
+    show_message(
+        format!("{}. {}", e, e.suggestion()),
+        Some(NotificationType::Error),
+    );
+    error.set(Some(e));
 }
@@ -0,0 +1,343 @@
+//! Merging copies of the same public key from several sources, such as a
+//! keyserver, WKD and an email attachment, into one key. Every user ID,
+//! subkey and signature is kept once, in the order first seen. As with
+//! `gpg --import`, signatures by the primary key that do not verify are
+//! dropped, and so are user IDs and subkeys left without a valid
+//! self-signature.
+
+use crate::audit::{self, Signed};
+use crate::{fingerprint, packets, paperkey};
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_SECRET_SUBKEY: u8 = 7;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_USER_ATTRIBUTE: u8 = 17;
+
+const SIG_CERTIFICATIONS: [u8; 4] = [0x10, 0x11, 0x12, 0x13];
+const SIG_SUBKEY_BINDING: u8 = 0x18;
+
+/// What merging added to the first key and what it dropped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// Number of keys that were merged.
+    pub sources: usize,
+    pub added_user_ids: Vec<String>,
+    /// Key IDs of the added subkeys.
+    pub added_subkeys: Vec<String>,
+    /// Certifications, bindings and revocations the first key lacked.
+    pub added_signatures: usize,
+    /// Signatures dropped because they were already present.
+    pub duplicate_signatures: usize,
+    /// User IDs dropped because no self-signature on them verifies.
+    pub dropped_user_ids: Vec<String>,
+    /// Key IDs of the subkeys dropped because no binding signature on them
+    /// verifies.
+    pub dropped_subkeys: Vec<String>,
+    /// Signatures that do not verify against the primary key, such as
+    /// forged revocations, with what they were on. Certifications of user
+    /// IDs by other keys are not checked.
+    pub dropped_signatures: Vec<String>,
+}
+
+impl Report {
+    pub fn to_text(&self) -> String {
+        let mut out = format!("Merged {} copies of the key\n", self.sources);
+        for user_id in &self.added_user_ids {
+            out.push_str(&format!("Added user ID {:?}\n", user_id));
+        }
+        for key_id in &self.added_subkeys {
+            out.push_str(&format!("Added subkey {}\n", key_id));
+        }
+        out.push_str(&format!("Added {} signatures\n", self.added_signatures));
+        for user_id in &self.dropped_user_ids {
+            out.push_str(&format!(
+                "Dropped user ID {:?} without a valid self-signature\n",
+                user_id
+            ));
+        }
+        for key_id in &self.dropped_subkeys {
+            out.push_str(&format!("Dropped subkey {} without a valid binding signature\n", key_id));
+        }
+        for sig in &self.dropped_signatures {
+            out.push_str(&format!("Dropped invalid {}\n", sig));
+        }
+        out.push_str(&format!(
+            "Dropped {} duplicate signatures",
+            self.duplicate_signatures
+        ));
+        out
+    }
+}
+
+/// A key, user ID or user attribute packet with the signatures on it.
+struct Component {
+    tag: u8,
+    body: Vec<u8>,
+    /// Whether a copy after the first one brought it in.
+    added: bool,
+    /// Signature packet bodies, each with whether a copy after the first
+    /// one brought it in.
+    sigs: Vec<(Vec<u8>, bool)>,
+}
+
+impl Component {
+    fn new(tag: u8, body: &[u8], added: bool) -> Self {
+        Component {
+            tag,
+            body: body.to_vec(),
+            added,
+            sigs: Vec::new(),
+        }
+    }
+
+    /// Adds the signatures of `other` that are missing here.
+    fn merge_sigs(&mut self, other: Component, added: bool, report: &mut Report) {
+        for (sig, _) in other.sigs {
+            let identity = signature_identity(&sig);
+            if self.sigs.iter().any(|(existing, _)| signature_identity(existing) == identity) {
+                report.duplicate_signatures += 1;
+            } else {
+                self.sigs.push((sig, added));
+            }
+        }
+    }
+
+    /// The user ID, or what else the component is, for the report.
+    fn name(&self) -> Result<String, String> {
+        Ok(match self.tag {
+            TAG_USER_ID => String::from_utf8_lossy(&self.body).into_owned(),
+            TAG_USER_ATTRIBUTE => "photo ID".to_string(),
+            TAG_PUBLIC_SUBKEY => format!("subkey {}", key_id(&self.body)?),
+            _ => "the primary key".to_string(),
+        })
+    }
+
+    /// Whether a signature of one of `kinds` by the key with the
+    /// fingerprint `primary` is left.
+    fn has_self_sig(&self, primary: &[u8], kinds: &[u8]) -> bool {
+        self.sigs.iter().any(|(sig, _)| {
+            sig.get(1).is_some_and(|kind| kinds.contains(kind)) && audit::issued_by(sig, primary)
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        paperkey::write_packet(out, self.tag, &self.body);
+        for (sig, _) in &self.sigs {
+            paperkey::write_packet(out, TAG_SIGNATURE, sig);
+        }
+    }
+}
+
+/// What tells two copies of a signature apart: everything but the unhashed
+/// subpackets, which anyone can change without breaking the signature.
+fn signature_identity(sig: &[u8]) -> Vec<u8> {
+    match audit::subpacket_areas(sig) {
+        Ok((hashed, unhashed)) => [&sig[..hashed.end], &sig[unhashed.end..]].concat(),
+        Err(_) => sig.to_vec(),
+    }
+}
+
+struct Cert {
+    primary: Component,
+    user_ids: Vec<Component>,
+    subkeys: Vec<Component>,
+}
+
+impl Cert {
+    fn fingerprint(&self) -> Result<Vec<u8>, String> {
+        key_fingerprint(&self.primary.body)
+    }
+
+    /// Drops the signatures that do not verify against the primary key,
+    /// then the user IDs and subkeys left without a valid
+    /// self-signature. Certifications of user IDs by other keys are kept,
+    /// as their keys are not at hand to check them.
+    fn verify(&mut self, report: &mut Report) -> Result<(), String> {
+        let fingerprint = self.fingerprint()?;
+        let primary = self.primary.body.clone();
+        if !audit::can_verify(&primary) {
+            return Err("The signatures of this kind of key cannot be checked".to_string());
+        }
+
+        drop_invalid(&mut self.primary, &primary, &Signed::Key, None, report)?;
+        let mut user_ids = Vec::new();
+        for mut user_id in std::mem::take(&mut self.user_ids) {
+            let body = user_id.body.clone();
+            let signed = match user_id.tag {
+                TAG_USER_ID => Signed::UserId(&body),
+                _ => Signed::UserAttribute(&body),
+            };
+            drop_invalid(&mut user_id, &primary, &signed, Some(&fingerprint), report)?;
+            if user_id.has_self_sig(&fingerprint, &SIG_CERTIFICATIONS) {
+                user_ids.push(user_id);
+            } else {
+                report.dropped_user_ids.push(user_id.name()?);
+            }
+        }
+        self.user_ids = user_ids;
+
+        let mut subkeys = Vec::new();
+        for mut subkey in std::mem::take(&mut self.subkeys) {
+            let body = subkey.body.clone();
+            drop_invalid(&mut subkey, &primary, &Signed::Subkey(&body), None, report)?;
+            if subkey.has_self_sig(&fingerprint, &[SIG_SUBKEY_BINDING]) {
+                subkeys.push(subkey);
+            } else {
+                report.dropped_subkeys.push(key_id(&subkey.body)?);
+            }
+        }
+        self.subkeys = subkeys;
+        Ok(())
+    }
+}
+
+/// Drops the signatures on `component` that do not verify against
+/// `primary`. With the fingerprint `only_by` set, signatures that name
+/// another key as their issuer are left alone.
+fn drop_invalid(
+    component: &mut Component,
+    primary: &[u8],
+    signed: &Signed,
+    only_by: Option<&[u8]>,
+    report: &mut Report,
+) -> Result<(), String> {
+    let name = component.name()?;
+    component.sigs.retain(|(sig, _)| {
+        if only_by.is_some_and(|fingerprint| !audit::issued_by(sig, fingerprint)) {
+            return true;
+        }
+        let valid = audit::verify(primary, signed, sig);
+        if !valid {
+            let kind = packets::signature_type(sig.get(1).copied().unwrap_or_default());
+            report.dropped_signatures.push(format!("signature {} on {}", kind, name));
+        }
+        valid
+    });
+    Ok(())
+}
+
+fn key_fingerprint(body: &[u8]) -> Result<Vec<u8>, String> {
+    paperkey::fingerprint(&body[..paperkey::public_key_len(body)?])
+}
+
+fn key_id(body: &[u8]) -> Result<String, String> {
+    Ok(fingerprint::to_hex(paperkey::key_id(&key_fingerprint(body)?)))
+}
+
+/// Splits binary data holding one or more public keys into certificates.
+fn parse(data: &[u8]) -> Result<Vec<Cert>, String> {
+    let mut certs: Vec<Cert> = Vec::new();
+    for packet in paperkey::parse_packets(data)? {
+        if packet.tag == TAG_PUBLIC_KEY {
+            certs.push(Cert {
+                primary: Component::new(packet.tag, packet.body, false),
+                user_ids: Vec::new(),
+                subkeys: Vec::new(),
+            });
+            continue;
+        }
+        if packet.tag == TAG_SECRET_KEY || packet.tag == TAG_SECRET_SUBKEY {
+            return Err("Only public keys can be merged".to_string());
+        }
+        let cert = certs.last_mut().ok_or("The data does not start with a public key")?;
+        match packet.tag {
+            TAG_USER_ID | TAG_USER_ATTRIBUTE => {
+                cert.user_ids.push(Component::new(packet.tag, packet.body, false))
+            }
+            TAG_PUBLIC_SUBKEY => {
+                cert.subkeys.push(Component::new(packet.tag, packet.body, false))
+            }
+            TAG_SIGNATURE => {
+                let current = match cert.subkeys.last_mut() {
+                    Some(subkey) => subkey,
+                    None => cert.user_ids.last_mut().unwrap_or(&mut cert.primary),
+                };
+                current.sigs.push((packet.body.to_vec(), false));
+            }
+            // Trust packets are local to the keyring they came from and
+            // marker packets carry nothing.
+            _ => {}
+        }
+    }
+    Ok(certs)
+}
+
+/// Merges binary copies of one public key into a single binary key. The
+/// copies may be concatenated in any order but must share the primary
+/// key.
+pub fn merge(data: &[u8]) -> Result<(Vec<u8>, Report), String> {
+    let mut certs = parse(data)?.into_iter();
+    let first = certs.next().ok_or("No public key found")?;
+    let primary = first.fingerprint()?;
+    let mut merged = Cert {
+        primary: Component::new(first.primary.tag, &first.primary.body, false),
+        user_ids: Vec::new(),
+        subkeys: Vec::new(),
+    };
+    let mut report = Report::default();
+
+    for (i, cert) in std::iter::once(first).chain(certs).enumerate() {
+        let other = cert.fingerprint()?;
+        if other != primary {
+            return Err(format!(
+                "Key {} is a different key than {}, only copies of the same key can be merged",
+                fingerprint::to_hex(&other),
+                fingerprint::to_hex(&primary)
+            ));
+        }
+        report.sources += 1;
+        let added = i > 0;
+        merged.primary.merge_sigs(cert.primary, added, &mut report);
+        for user_id in cert.user_ids {
+            let existing = merged
+                .user_ids
+                .iter_mut()
+                .find(|u| u.tag == user_id.tag && u.body == user_id.body);
+            match existing {
+                Some(existing) => existing.merge_sigs(user_id, added, &mut report),
+                None => {
+                    let mut new = Component::new(user_id.tag, &user_id.body, added);
+                    new.merge_sigs(user_id, added, &mut report);
+                    merged.user_ids.push(new);
+                }
+            }
+        }
+        for subkey in cert.subkeys {
+            let existing = merged.subkeys.iter_mut().find(|s| s.body == subkey.body);
+            match existing {
+                Some(existing) => existing.merge_sigs(subkey, added, &mut report),
+                None => {
+                    let mut new = Component::new(subkey.tag, &subkey.body, added);
+                    new.merge_sigs(subkey, added, &mut report);
+                    merged.subkeys.push(new);
+                }
+            }
+        }
+    }
+    merged.verify(&mut report)?;
+
+    // Only what survived the checks counts as added.
+    for user_id in merged.user_ids.iter().filter(|u| u.added) {
+        report.added_user_ids.push(user_id.name()?);
+    }
+    for subkey in merged.subkeys.iter().filter(|s| s.added) {
+        report.added_subkeys.push(key_id(&subkey.body)?);
+    }
+    let components = std::iter::once(&merged.primary)
+        .chain(&merged.user_ids)
+        .chain(&merged.subkeys);
+    report.added_signatures = components
+        .flat_map(|component| &component.sigs)
+        .filter(|(_, added)| *added)
+        .count();
+
+    let mut out = Vec::new();
+    merged.primary.write(&mut out);
+    for component in merged.user_ids.iter().chain(&merged.subkeys) {
+        component.write(&mut out);
+    }
+    Ok((out, report))
+}
@@ -0,0 +1,865 @@
+//! Packet-level listing of OpenPGP data, similar to `gpg --list-packets`.
+//! Secret key material and message contents are never included, only their
+//! sizes, so a listing is safe to share when asking for help.
+
+use std::borrow::Cow;
+
+use crate::{compression, fingerprint, paperkey};
+
+/// Decompressed data beyond this size is not listed.
+const MAX_DECOMPRESSED: u64 = 16 * 1024 * 1024;
+/// Compressed packets nested deeper than this are not opened.
+const MAX_DEPTH: usize = 8;
+
+/// One packet with a readable description of its fields.
+#[derive(Debug, PartialEq)]
+pub struct Packet {
+    pub tag: u8,
+    /// Offset of the packet header within the data it was found in.
+    pub offset: usize,
+    /// Length of the packet body.
+    pub length: usize,
+    /// Whether the body was split into partial body lengths.
+    pub partial: bool,
+    /// Version octet of packets that start with one, e.g. 4 for RFC 4880
+    /// keys and signatures and 6 for RFC 9580 ones.
+    pub version: Option<u8>,
+    pub details: Vec<String>,
+    /// Packets found inside a compressed data packet.
+    pub children: Vec<Packet>,
+}
+
+impl Packet {
+    pub fn name(&self) -> &'static str {
+        match self.tag {
+            1 => "Public-Key Encrypted Session Key",
+            2 => "Signature",
+            3 => "Symmetric-Key Encrypted Session Key",
+            4 => "One-Pass Signature",
+            5 => "Secret Key",
+            6 => "Public Key",
+            7 => "Secret Subkey",
+            8 => "Compressed Data",
+            9 => "Symmetrically Encrypted Data",
+            10 => "Marker",
+            11 => "Literal Data",
+            12 => "Trust",
+            13 => "User ID",
+            14 => "Public Subkey",
+            17 => "User Attribute",
+            18 => "Symmetrically Encrypted Integrity Protected Data",
+            19 => "Modification Detection Code",
+            20 => "AEAD Encrypted Data",
+            21 => "Padding",
+            _ => "Unknown",
+        }
+    }
+}
+
+/// Parses binary OpenPGP data into a packet tree. Malformed packet bodies
+/// are reported in the packet details; only a broken packet framing stops
+/// the listing.
+pub fn parse(data: &[u8]) -> Result<Vec<Packet>, String> {
+    parse_at_depth(data, 0)
+}
+
+/// The session key packets that start a binary encrypted message.
+#[derive(Debug, Default)]
+pub(crate) struct SessionKeys {
+    /// Key IDs of the recipients, all zeros for anonymous ones.
+    pub(crate) key_ids: Vec<Vec<u8>>,
+    /// Whether the message can be decrypted with a passphrase.
+    pub(crate) passphrase: bool,
+    /// Whether they are v6 packets, which come with SEIPD v2 data.
+    pub(crate) rfc9580: bool,
+}
+
+/// Reads the session key packets of a binary message, stopping at the
+/// first packet of another kind.
+pub(crate) fn session_keys(data: &[u8]) -> Result<SessionKeys, String> {
+    let mut keys = SessionKeys::default();
+    let mut pos = 0;
+    while let Some(&header) = data.get(pos) {
+        let tag = if header & 0x40 != 0 {
+            header & 0x3f
+        } else {
+            (header >> 2) & 0x0f
+        };
+        // Marker packets may come first and are ignored.
+        if !matches!(tag, 1 | 3 | 10) {
+            break;
+        }
+        let (_, body, _) = read_packet(data, &mut pos)?;
+        let mut r = Reader::new(&body);
+        match (tag, r.u8()?) {
+            (1, 3) => keys.key_ids.push(r.bytes(8)?.to_vec()),
+            (1, 6) => {
+                let recipient = match r.u8()? as usize {
+                    0 => vec![0; 8],
+                    len => paperkey::key_id(r.bytes(len)?.get(1..).unwrap_or_default()).to_vec(),
+                };
+                keys.key_ids.push(recipient);
+                keys.rfc9580 = true;
+            }
+            (3, version) => {
+                keys.passphrase = true;
+                keys.rfc9580 |= version == 6;
+            }
+            _ => {}
+        }
+    }
+    Ok(keys)
+}
+
+/// Renders packets as an indented tree.
+pub fn to_text(packets: &[Packet]) -> String {
+    let mut out = String::new();
+    write_tree(&mut out, packets, 0);
+    out
+}
+
+fn write_tree(out: &mut String, packets: &[Packet], indent: usize) {
+    let pad = " ".repeat(indent);
+    for packet in packets {
+        out.push_str(&format!(
+            "{}:offset {}: {} (tag {}), {} bytes{}\n",
+            pad,
+            packet.offset,
+            packet.name(),
+            packet.tag,
+            packet.length,
+            if packet.partial { ", partial lengths" } else { "" }
+        ));
+        for detail in &packet.details {
+            out.push_str(&format!("{}    {}\n", pad, detail));
+        }
+        write_tree(out, &packet.children, indent + 4);
+    }
+}
+
+fn parse_at_depth(data: &[u8], depth: usize) -> Result<Vec<Packet>, String> {
+    let mut packets = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let offset = pos;
+        let (tag, body, partial) = read_packet(data, &mut pos)?;
+        let mut packet = Packet {
+            tag,
+            offset,
+            length: body.len(),
+            partial,
+            version: match tag {
+                1..=7 | 14 | 18 | 20 => body.first().copied(),
+                _ => None,
+            },
+            details: Vec::new(),
+            children: Vec::new(),
+        };
+        if let Err(e) = describe(&mut packet, &body, depth) {
+            packet.details.push(format!("malformed: {}", e));
+        }
+        packets.push(packet);
+    }
+    Ok(packets)
+}
+
+/// Reads the packet starting at `pos`, joining partial body chunks, and
+/// moves `pos` past it.
+fn read_packet<'a>(
+    data: &'a [u8],
+    pos: &mut usize,
+) -> Result<(u8, Cow<'a, [u8]>, bool), String> {
+    let start = *pos;
+    let truncated = || format!("Packet at offset {} is truncated", start);
+    let take = move |pos: &mut usize, len: usize| -> Result<&'a [u8], String> {
+        let end = pos.checked_add(len).ok_or_else(truncated)?;
+        let bytes = data.get(*pos..end).ok_or_else(truncated)?;
+        *pos = end;
+        Ok(bytes)
+    };
+
+    let header = take(pos, 1)?[0];
+    if header & 0x80 == 0 {
+        return Err(format!("Invalid packet header at offset {}", start));
+    }
+
+    if header & 0x40 == 0 {
+        let tag = (header >> 2) & 0x0f;
+        let len = match header & 0x03 {
+            0 => take(pos, 1)?[0] as usize,
+            1 => {
+                let b = take(pos, 2)?;
+                u16::from_be_bytes([b[0], b[1]]) as usize
+            }
+            2 => {
+                let b = take(pos, 4)?;
+                u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize
+            }
+            _ => data.len() - *pos,
+        };
+        return Ok((tag, Cow::Borrowed(take(pos, len)?), false));
+    }
+
+    let tag = header & 0x3f;
+    let mut chunks: Vec<u8> = Vec::new();
+    let mut partial = false;
+    loop {
+        let first = take(pos, 1)?[0] as usize;
+        let (len, more) = match first {
+            0..=191 => (first, false),
+            192..=223 => (((first - 192) << 8) + take(pos, 1)?[0] as usize + 192, false),
+            224..=254 => (1 << (first & 0x1f), true),
+            _ => {
+                let b = take(pos, 4)?;
+                (u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize, false)
+            }
+        };
+        let chunk = take(pos, len)?;
+        if !more && !partial {
+            return Ok((tag, Cow::Borrowed(chunk), false));
+        }
+        chunks.extend_from_slice(chunk);
+        partial = true;
+        if !more {
+            return Ok((tag, Cow::Owned(chunks), true));
+        }
+    }
+}
+
+/// Sequential reader over a packet body.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len).ok_or("truncated")?;
+        let bytes = self.data.get(self.pos..end).ok_or("truncated")?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.pos.min(self.data.len())..];
+        self.pos = self.data.len();
+        rest
+    }
+}
+
+fn describe(packet: &mut Packet, body: &[u8], depth: usize) -> Result<(), String> {
+    let details = &mut packet.details;
+    let mut r = Reader::new(body);
+    match packet.tag {
+        1 => {
+            let version = r.u8()?;
+            details.push(format!("version {}", version));
+            if version == 3 {
+                let key_id = r.bytes(8)?;
+                if key_id.iter().all(|&b| b == 0) {
+                    details.push("key ID 0000000000000000 (hidden recipient)".to_string());
+                } else {
+                    details.push(format!("key ID {}", fingerprint::to_hex(key_id)));
+                }
+                details.push(format!("algorithm {}", public_key_algorithm(r.u8()?)));
+                details.push(format!("encrypted session key: {} bytes", r.rest().len()));
+            } else if version == 6 {
+                let recipient_len = r.u8()? as usize;
+                if recipient_len == 0 {
+                    details.push("anonymous recipient".to_string());
+                } else {
+                    let recipient = r.bytes(recipient_len)?;
+                    details.push(format!(
+                        "recipient v{} key {}",
+                        recipient[0],
+                        fingerprint::to_hex(&recipient[1..])
+                    ));
+                }
+                details.push(format!("algorithm {}", public_key_algorithm(r.u8()?)));
+                details.push(format!("encrypted session key: {} bytes", r.rest().len()));
+            }
+        }
+        2 => describe_signature(details, &mut r)?,
+        3 => {
+            let version = r.u8()?;
+            details.push(format!("version {}", version));
+            if version == 4 {
+                details.push(format!("cipher {}", symmetric_algorithm(r.u8()?)));
+                details.push(describe_s2k(&mut r)?);
+                let rest = r.rest();
+                if !rest.is_empty() {
+                    details.push(format!("encrypted session key: {} bytes", rest.len()));
+                }
+            } else if version == 6 {
+                r.u8()?;
+                details.push(format!("cipher {}", symmetric_algorithm(r.u8()?)));
+                details.push(format!("AEAD {}", aead_algorithm(r.u8()?)));
+                let s2k_len = r.u8()? as usize;
+                details.push(describe_s2k(&mut Reader::new(r.bytes(s2k_len)?))?);
+                details.push(format!("encrypted session key: {} bytes", r.rest().len()));
+            }
+        }
+        4 => {
+            let version = r.u8()?;
+            details.push(format!("version {}", version));
+            if version == 3 {
+                details.push(format!("type {}", signature_type(r.u8()?)));
+                details.push(format!("hash {}", hash_algorithm(r.u8()?)));
+                details.push(format!("algorithm {}", public_key_algorithm(r.u8()?)));
+                details.push(format!("key ID {}", fingerprint::to_hex(r.bytes(8)?)));
+                details.push(format!("last: {}", r.u8()? != 0));
+            }
+        }
+        5..=7 | 14 => describe_key(details, packet.tag, body)?,
+        8 => {
+            let algorithm = r.u8()?;
+            details.push(format!("algorithm {}", compression_algorithm(algorithm)));
+            let Some(data) = compression::inflate(algorithm, r.rest(), MAX_DECOMPRESSED)? else {
+                details.push("contents not listed: unsupported compression".to_string());
+                return Ok(());
+            };
+            details.push(format!("decompressed: {} bytes", data.len()));
+            if depth >= MAX_DEPTH {
+                details.push("contents not listed: nested too deep".to_string());
+            } else {
+                match parse_at_depth(&data, depth + 1) {
+                    Ok(children) => packet.children = children,
+                    Err(e) => details.push(format!("contents not listed: {}", e)),
+                }
+            }
+        }
+        9 => details.push(format!(
+            "encrypted data: {} bytes, no integrity protection",
+            body.len()
+        )),
+        11 => {
+            let format = r.u8()?;
+            details.push(format!(
+                "format {}",
+                match format {
+                    b'b' => "binary",
+                    b't' => "text",
+                    b'u' => "UTF-8 text",
+                    _ => "unknown",
+                }
+            ));
+            let name_len = r.u8()? as usize;
+            let name = String::from_utf8_lossy(r.bytes(name_len)?);
+            details.push(format!("file name {:?}", name));
+            details.push(format!("date {}", format_time(r.u32()?)));
+            details.push(format!("content: {} bytes (not shown)", r.rest().len()));
+        }
+        13 => details.push(format!("{:?}", String::from_utf8_lossy(body))),
+        17 => details.push(format!("attribute data: {} bytes", body.len())),
+        18 => {
+            let version = r.u8()?;
+            details.push(format!("version {}", version));
+            if version == 2 {
+                details.push(format!("cipher {}", symmetric_algorithm(r.u8()?)));
+                details.push(format!("AEAD {}", aead_algorithm(r.u8()?)));
+                details.push(chunk_size(r.u8()?));
+                r.bytes(32)?;
+            }
+            details.push(format!("encrypted data: {} bytes", r.rest().len()));
+        }
+        20 => {
+            let version = r.u8()?;
+            details.push(format!("version {}", version));
+            details.push(format!("cipher {}", symmetric_algorithm(r.u8()?)));
+            details.push(format!("AEAD {}", aead_algorithm(r.u8()?)));
+            details.push(chunk_size(r.u8()?));
+            details.push(format!("encrypted data: {} bytes", r.rest().len()));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn describe_key(details: &mut Vec<String>, tag: u8, body: &[u8]) -> Result<(), String> {
+    let mut r = Reader::new(body);
+    let version = r.u8()?;
+    details.push(format!("version {}", version));
+    if version == 6 {
+        return describe_v6_key(details, tag, body);
+    }
+    if version != 4 {
+        return Ok(());
+    }
+    details.push(format!("created {}", format_time(r.u32()?)));
+    let algorithm = r.u8()?;
+    match algorithm {
+        1..=3 => details.push(format!(
+            "algorithm {}, {} bits",
+            public_key_algorithm(algorithm),
+            r.u16()?
+        )),
+        18 | 19 | 22 => {
+            let oid_len = r.u8()? as usize;
+            details.push(format!(
+                "algorithm {}, curve {}",
+                public_key_algorithm(algorithm),
+                curve_name(r.bytes(oid_len)?)
+            ));
+        }
+        _ => details.push(format!("algorithm {}", public_key_algorithm(algorithm))),
+    }
+
+    let public_len = paperkey::public_key_len(body)?;
+    let fpr = paperkey::fingerprint(&body[..public_len])?;
+    details.push(format!("fingerprint {}", fingerprint::to_hex(&fpr)));
+    details.push(format!("key ID {}", fingerprint::to_hex(paperkey::key_id(&fpr))));
+
+    if tag == 5 || tag == 7 {
+        let mut r = Reader::new(&body[public_len..]);
+        let usage = r.u8()?;
+        match usage {
+            0 => details.push("protection: none".to_string()),
+            254 | 255 => {
+                let cipher = r.u8()?;
+                details.push(format!(
+                    "protection: {}, {}",
+                    symmetric_algorithm(cipher),
+                    describe_s2k(&mut r)?
+                ));
+            }
+            cipher => details.push(format!("protection: {}", symmetric_algorithm(cipher))),
+        }
+        details.push(format!(
+            "secret key material: {} bytes (not shown)",
+            r.rest().len()
+        ));
+    }
+    Ok(())
+}
+
+/// RFC 9580 keys state the length of their key material, so the public
+/// part can be found without knowing the algorithm.
+fn describe_v6_key(details: &mut Vec<String>, tag: u8, body: &[u8]) -> Result<(), String> {
+    let mut r = Reader::new(&body[1..]);
+    details.push(format!("created {}", format_time(r.u32()?)));
+    let algorithm = r.u8()?;
+    let material_len = r.u32()? as usize;
+    match algorithm {
+        18 | 19 | 22 => {
+            let mut m = Reader::new(r.bytes(material_len)?);
+            let oid_len = m.u8()? as usize;
+            details.push(format!(
+                "algorithm {}, curve {}",
+                public_key_algorithm(algorithm),
+                curve_name(m.bytes(oid_len)?)
+            ));
+        }
+        _ => {
+            r.bytes(material_len)?;
+            details.push(format!("algorithm {}", public_key_algorithm(algorithm)));
+        }
+    }
+
+    let public_len = 1 + r.pos;
+    let fpr = paperkey::fingerprint(&body[..public_len])?;
+    details.push(format!("fingerprint {}", fingerprint::to_hex(&fpr)));
+    details.push(format!("key ID {}", fingerprint::to_hex(paperkey::key_id(&fpr))));
+
+    if tag == 5 || tag == 7 {
+        let mut r = Reader::new(&body[public_len..]);
+        match r.u8()? {
+            0 => details.push("protection: none".to_string()),
+            usage @ (253 | 254) => {
+                r.u8()?;
+                let cipher = symmetric_algorithm(r.u8()?);
+                let aead = if usage == 253 {
+                    format!(", AEAD {}", aead_algorithm(r.u8()?))
+                } else {
+                    String::new()
+                };
+                let s2k_len = r.u8()? as usize;
+                let s2k = describe_s2k(&mut Reader::new(r.bytes(s2k_len)?))?;
+                details.push(format!("protection: {}{}, {}", cipher, aead, s2k));
+            }
+            usage => details.push(format!("protection: usage {}", usage)),
+        }
+        details.push(format!(
+            "secret key material: {} bytes (not shown)",
+            r.rest().len()
+        ));
+    }
+    Ok(())
+}
+
+fn describe_s2k(r: &mut Reader) -> Result<String, String> {
+    let kind = r.u8()?;
+    Ok(match kind {
+        0 => format!("S2K simple, {}", hash_algorithm(r.u8()?)),
+        1 => {
+            let hash = r.u8()?;
+            r.bytes(8)?;
+            format!("S2K salted, {}", hash_algorithm(hash))
+        }
+        3 => {
+            let hash = r.u8()?;
+            r.bytes(8)?;
+            let coded = r.u8()? as u32;
+            let count = (16 + (coded & 15)) << ((coded >> 4) + 6);
+            format!(
+                "S2K iterated and salted, {}, {} bytes hashed",
+                hash_algorithm(hash),
+                count
+            )
+        }
+        4 => "S2K Argon2".to_string(),
+        101 => "S2K GNU dummy (no secret key material)".to_string(),
+        _ => format!("S2K type {}", kind),
+    })
+}
+
+fn describe_signature(details: &mut Vec<String>, r: &mut Reader) -> Result<(), String> {
+    let version = r.u8()?;
+    details.push(format!("version {}", version));
+    match version {
+        3 => {
+            r.u8()?;
+            details.push(format!("type {}", signature_type(r.u8()?)));
+            details.push(format!("created {}", format_time(r.u32()?)));
+            details.push(format!("key ID {}", fingerprint::to_hex(r.bytes(8)?)));
+            details.push(format!("algorithm {}", public_key_algorithm(r.u8()?)));
+            details.push(format!("hash {}", hash_algorithm(r.u8()?)));
+        }
+        4 => {
+            details.push(format!("type {}", signature_type(r.u8()?)));
+            details.push(format!("algorithm {}", public_key_algorithm(r.u8()?)));
+            details.push(format!("hash {}", hash_algorithm(r.u8()?)));
+            let hashed_len = r.u16()? as usize;
+            for subpacket in subpackets(r.bytes(hashed_len)?)? {
+                details.push(format!("hashed: {}", subpacket));
+            }
+            let unhashed_len = r.u16()? as usize;
+            for subpacket in subpackets(r.bytes(unhashed_len)?)? {
+                details.push(format!("unhashed: {}", subpacket));
+            }
+            let left = r.bytes(2)?;
+            details.push(format!("hash prefix {:02X}{:02X}", left[0], left[1]));
+        }
+        6 => {
+            details.push(format!("type {}", signature_type(r.u8()?)));
+            details.push(format!("algorithm {}", public_key_algorithm(r.u8()?)));
+            details.push(format!("hash {}", hash_algorithm(r.u8()?)));
+            let hashed_len = r.u32()? as usize;
+            for subpacket in subpackets(r.bytes(hashed_len)?)? {
+                details.push(format!("hashed: {}", subpacket));
+            }
+            let unhashed_len = r.u32()? as usize;
+            for subpacket in subpackets(r.bytes(unhashed_len)?)? {
+                details.push(format!("unhashed: {}", subpacket));
+            }
+            let left = r.bytes(2)?;
+            details.push(format!("hash prefix {:02X}{:02X}", left[0], left[1]));
+            let salt_len = r.u8()? as usize;
+            details.push(format!("salt: {} bytes", r.bytes(salt_len)?.len()));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn subpackets(data: &[u8]) -> Result<Vec<String>, String> {
+    let mut r = Reader::new(data);
+    let mut out = Vec::new();
+    while r.pos < data.len() {
+        let first = r.u8()? as usize;
+        let len = match first {
+            0..=191 => first,
+            192..=254 => ((first - 192) << 8) + r.u8()? as usize + 192,
+            _ => r.u32()? as usize,
+        };
+        if len == 0 {
+            return Err("empty signature subpacket".to_string());
+        }
+        let kind = r.u8()?;
+        let body = r.bytes(len - 1)?;
+        let critical = if kind & 0x80 != 0 { " (critical)" } else { "" };
+        out.push(format!("{}{}", describe_subpacket(kind & 0x7f, body)?, critical));
+    }
+    Ok(out)
+}
+
+fn describe_subpacket(kind: u8, body: &[u8]) -> Result<String, String> {
+    let mut r = Reader::new(body);
+    let list = |f: fn(u8) -> String| body.iter().map(|&b| f(b)).collect::<Vec<_>>().join(", ");
+    Ok(match kind {
+        2 => format!("signature created {}", format_time(r.u32()?)),
+        3 => format!("signature expires {}", format_duration(r.u32()?)),
+        9 => format!("key expires {}", format_duration(r.u32()?)),
+        11 => format!("preferred ciphers {}", list(symmetric_algorithm)),
+        16 => format!("issuer key ID {}", fingerprint::to_hex(body)),
+        20 => format!("notation, {} bytes", body.len()),
+        21 => format!("preferred hashes {}", list(hash_algorithm)),
+        22 => format!("preferred compression {}", list(compression_algorithm)),
+        23 => "key server preferences".to_string(),
+        25 => format!("primary user ID: {}", body.first().is_some_and(|&b| b != 0)),
+        26 => format!("policy URI {:?}", String::from_utf8_lossy(body)),
+        27 => format!("key flags {}", key_flags(body.first().copied().unwrap_or(0))),
+        28 => format!("signer's user ID {:?}", String::from_utf8_lossy(body)),
+        29 => {
+            let code = r.u8()?;
+            format!(
+                "revocation reason {}: {:?}",
+                code,
+                String::from_utf8_lossy(r.rest())
+            )
+        }
+        30 => format!("features {}", features(body.first().copied().unwrap_or(0))),
+        32 => format!("embedded signature, {} bytes", body.len()),
+        33 => {
+            r.u8()?;
+            format!("issuer fingerprint {}", fingerprint::to_hex(r.rest()))
+        }
+        34 => format!("preferred AEAD {}", list(aead_algorithm)),
+        39 => format!("preferred AEAD ciphersuites, {} bytes", body.len()),
+        _ => format!("subpacket {}, {} bytes", kind, body.len()),
+    })
+}
+
+fn key_flags(flags: u8) -> String {
+    let names = [
+        (0x01, "certify"),
+        (0x02, "sign"),
+        (0x04, "encrypt communications"),
+        (0x08, "encrypt storage"),
+        (0x10, "split key"),
+        (0x20, "authenticate"),
+        (0x80, "shared key"),
+    ];
+    let set: Vec<&str> = names
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if set.is_empty() {
+        "none".to_string()
+    } else {
+        set.join(", ")
+    }
+}
+
+fn features(flags: u8) -> String {
+    let names = [(0x01, "MDC"), (0x02, "AEAD"), (0x08, "SEIPDv2")];
+    let set: Vec<&str> = names
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if set.is_empty() {
+        "none".to_string()
+    } else {
+        set.join(", ")
+    }
+}
+
+fn public_key_algorithm(id: u8) -> String {
+    match id {
+        1 => "RSA".to_string(),
+        2 => "RSA (encrypt only)".to_string(),
+        3 => "RSA (sign only)".to_string(),
+        16 => "ElGamal".to_string(),
+        17 => "DSA".to_string(),
+        18 => "ECDH".to_string(),
+        19 => "ECDSA".to_string(),
+        20 => "ElGamal (encrypt or sign)".to_string(),
+        22 => "EdDSA".to_string(),
+        25 => "X25519".to_string(),
+        26 => "X448".to_string(),
+        27 => "Ed25519".to_string(),
+        28 => "Ed448".to_string(),
+        _ => format!("unknown ({})", id),
+    }
+}
+
+pub(crate) fn symmetric_algorithm(id: u8) -> String {
+    match id {
+        0 => "plaintext".to_string(),
+        1 => "IDEA".to_string(),
+        2 => "TripleDES".to_string(),
+        3 => "CAST5".to_string(),
+        4 => "Blowfish".to_string(),
+        7 => "AES-128".to_string(),
+        8 => "AES-192".to_string(),
+        9 => "AES-256".to_string(),
+        10 => "Twofish".to_string(),
+        11 => "Camellia-128".to_string(),
+        12 => "Camellia-192".to_string(),
+        13 => "Camellia-256".to_string(),
+        _ => format!("unknown ({})", id),
+    }
+}
+
+pub(crate) fn hash_algorithm(id: u8) -> String {
+    match id {
+        1 => "MD5".to_string(),
+        2 => "SHA-1".to_string(),
+        3 => "RIPEMD-160".to_string(),
+        8 => "SHA-256".to_string(),
+        9 => "SHA-384".to_string(),
+        10 => "SHA-512".to_string(),
+        11 => "SHA-224".to_string(),
+        12 => "SHA3-256".to_string(),
+        14 => "SHA3-512".to_string(),
+        _ => format!("unknown ({})", id),
+    }
+}
+
+pub(crate) fn compression_algorithm(id: u8) -> String {
+    match id {
+        0 => "uncompressed".to_string(),
+        1 => "ZIP".to_string(),
+        2 => "ZLIB".to_string(),
+        3 => "BZip2".to_string(),
+        _ => format!("unknown ({})", id),
+    }
+}
+
+fn aead_algorithm(id: u8) -> String {
+    match id {
+        1 => "EAX".to_string(),
+        2 => "OCB".to_string(),
+        3 => "GCM".to_string(),
+        _ => format!("unknown ({})", id),
+    }
+}
+
+pub(crate) fn signature_type(id: u8) -> String {
+    let name = match id {
+        0x00 => "binary document",
+        0x01 => "text document",
+        0x02 => "standalone",
+        0x10 => "generic certification",
+        0x11 => "persona certification",
+        0x12 => "casual certification",
+        0x13 => "positive certification",
+        0x18 => "subkey binding",
+        0x19 => "primary key binding",
+        0x1f => "direct key",
+        0x20 => "key revocation",
+        0x28 => "subkey revocation",
+        0x30 => "certification revocation",
+        0x40 => "timestamp",
+        0x50 => "third-party confirmation",
+        _ => "unknown",
+    };
+    format!("0x{:02x} ({})", id, name)
+}
+
+fn curve_name(oid: &[u8]) -> String {
+    match oid {
+        [0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01] => "Ed25519".to_string(),
+        [0x2b, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01] => "Curve25519".to_string(),
+        [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07] => "NIST P-256".to_string(),
+        [0x2b, 0x81, 0x04, 0x00, 0x22] => "NIST P-384".to_string(),
+        [0x2b, 0x81, 0x04, 0x00, 0x23] => "NIST P-521".to_string(),
+        _ => format!("OID {}", fingerprint::to_hex(oid)),
+    }
+}
+
+fn chunk_size(coded: u8) -> String {
+    match 1u64.checked_shl(u32::from(coded) + 6) {
+        Some(size) => format!("chunk size {} bytes", size),
+        None => format!("chunk size code {}", coded),
+    }
+}
+
+/// Formats a key or signature lifetime, where 0 means it never expires.
+fn format_duration(seconds: u32) -> String {
+    match seconds {
+        0 => "never".to_string(),
+        s if s % 86400 == 0 => format!("after {} days", s / 86400),
+        s => format!("after {} seconds", s),
+    }
+}
+
+/// Formats a Unix timestamp as a UTC date and time.
+pub fn format_time(timestamp: u32) -> String {
+    let days = i64::from(timestamp / 86400);
+    let seconds = timestamp % 86400;
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Parses a UTC date written as `YYYY-MM-DD`, optionally followed by
+/// `HH:MM` or `HH:MM:SS` after a space or `T`, into a Unix timestamp.
+pub fn parse_time(text: &str) -> Option<u32> {
+    let text = text.trim().trim_end_matches("UTC").trim_end().trim_end_matches('Z');
+    let (date, time) = match text.split_once([' ', 'T']) {
+        Some((date, time)) => (date, time),
+        None => (text, "00:00"),
+    };
+    let date: Vec<i64> = date.split('-').map(|n| n.parse().ok()).collect::<Option<_>>()?;
+    let time: Vec<i64> = time.split(':').map(|n| n.parse().ok()).collect::<Option<_>>()?;
+    let [year, month, day] = date[..] else {
+        return None;
+    };
+    let (hour, minute, second) = match time[..] {
+        [hour, minute] => (hour, minute, 0),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return None,
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if !(1..=12).contains(&month)
+        || !(1..=month_days).contains(&day)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&minute)
+        || !(0..60).contains(&second)
+    {
+        return None;
+    }
+
+    // Days since the epoch from a civil date, the inverse of format_time.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    u32::try_from(days * 86400 + hour * 3600 + minute * 60 + second).ok()
+}
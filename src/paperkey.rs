@@ -0,0 +1,488 @@
+//! Paperkey-style backups: only the secret parts of each secret key packet
+//! are kept, together with the fingerprint of the key they belong to. The
+//! public key supplies everything else when the backup is restored.
+
+use std::fmt::Write;
+
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::armor::crc24;
+
+const FORMAT_VERSION: u8 = 1;
+const BYTES_PER_LINE: usize = 20;
+const QR_CHUNK_SIZE: usize = 512;
+const QR_CHUNK_PREFIX: &str = "APT-PAPER";
+
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_SECRET_SUBKEY: u8 = 7;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+
+pub(crate) struct Packet<'a> {
+    pub(crate) tag: u8,
+    pub(crate) body: &'a [u8],
+}
+
+/// Secret material of one key packet, identified by its fingerprint.
+struct SecretPart {
+    fingerprint: Vec<u8>,
+    secret: Zeroizing<Vec<u8>>,
+}
+
+/// Extracts the secret material from a binary secret key.
+pub fn extract(secret_key: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    // The fingerprint and length that replace each public part are shorter
+    // than it, so the buffer never has to grow and leave copies behind.
+    let mut out = Zeroizing::new(Vec::with_capacity(secret_key.len() + 1));
+    out.push(FORMAT_VERSION);
+    let mut found = false;
+    for packet in parse_packets(secret_key)? {
+        if packet.tag != TAG_SECRET_KEY && packet.tag != TAG_SECRET_SUBKEY {
+            continue;
+        }
+        let public_len = public_key_len(packet.body)?;
+        let fingerprint = fingerprint(&packet.body[..public_len])?;
+        let secret = &packet.body[public_len..];
+        let secret_len = u16::try_from(secret.len()).map_err(|_| "Secret key part too large")?;
+
+        out.push(fingerprint.len() as u8);
+        out.extend_from_slice(&fingerprint);
+        out.extend_from_slice(&secret_len.to_be_bytes());
+        out.extend_from_slice(secret);
+        found = true;
+    }
+    if !found {
+        return Err("No secret key packets found".to_string());
+    }
+    Ok(out)
+}
+
+/// Rebuilds a binary secret key from a public key and extracted secret
+/// material. Public packets without a matching secret part are kept as-is.
+pub fn restore(public_key: &[u8], backup: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    let parts = parse_backup(backup)?;
+    let packets = parse_packets(public_key)?;
+    // Room for every packet with a new header of at most 6 bytes.
+    let capacity = public_key.len() + backup.len() + 6 * packets.len();
+    let mut out = Zeroizing::new(Vec::with_capacity(capacity));
+    let mut restored = 0;
+    for packet in packets {
+        let secret_tag = match packet.tag {
+            TAG_PUBLIC_KEY => TAG_SECRET_KEY,
+            TAG_PUBLIC_SUBKEY => TAG_SECRET_SUBKEY,
+            _ => {
+                write_packet(&mut out, packet.tag, packet.body);
+                continue;
+            }
+        };
+        let fingerprint = fingerprint(packet.body)?;
+        match parts.iter().find(|p| p.fingerprint == fingerprint) {
+            Some(part) => {
+                let mut body =
+                    Zeroizing::new(Vec::with_capacity(packet.body.len() + part.secret.len()));
+                body.extend_from_slice(packet.body);
+                body.extend_from_slice(&part.secret);
+                write_packet(&mut out, secret_tag, &body);
+                restored += 1;
+            }
+            None if packet.tag == TAG_PUBLIC_KEY => {
+                return Err("Backup does not belong to this public key".to_string());
+            }
+            None => write_packet(&mut out, packet.tag, packet.body),
+        }
+    }
+    if restored != parts.len() {
+        return Err(format!(
+            "Only {} of {} secret parts match the public key",
+            restored,
+            parts.len()
+        ));
+    }
+    Ok(out)
+}
+
+/// Renders backup data as numbered hex lines, each followed by its CRC24,
+/// with a final line holding the CRC24 of all data.
+pub fn to_text(backup: &[u8], fingerprint: &str) -> Zeroizing<String> {
+    // Written in place into a buffer large enough for the whole text: each
+    // byte takes three characters and each line at most 20 more.
+    let lines = backup.len().div_ceil(BYTES_PER_LINE) + 1;
+    let mut text = Zeroizing::new(String::with_capacity(512 + 3 * backup.len() + 20 * lines));
+    let _ = write!(
+        text,
+        "# Another PGP Tool paper backup\n\
+         # Secret key: {}\n\
+         # Each line: line number, {} bytes of hex data, CRC24 of the line.\n\
+         # The last line holds the CRC24 of all data.\n\
+         # Restore it together with the public key in the Backup tab.\n",
+        fingerprint, BYTES_PER_LINE
+    );
+    let mut line_no = 1;
+    for chunk in backup.chunks(BYTES_PER_LINE) {
+        let _ = write!(text, "{:3}:", line_no);
+        for byte in chunk {
+            let _ = write!(text, " {:02X}", byte);
+        }
+        let _ = writeln!(text, " {:06X}", crc24(chunk));
+        line_no += 1;
+    }
+    let _ = writeln!(text, "{:3}: {:06X}", line_no, crc24(backup));
+    text
+}
+
+/// Parses the text produced by [`to_text`] or a set of scanned QR chunks,
+/// reporting the first line whose checksum does not match.
+pub fn from_text(text: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    if text.contains(QR_CHUNK_PREFIX) {
+        return from_qr_chunks(text);
+    }
+
+    // Every byte takes at least two characters of the text.
+    let mut data = Zeroizing::new(Vec::with_capacity(text.len() / 2));
+    let mut total_crc = None;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (line_no, rest) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Malformed line: {}", line))?;
+        let line_no = line_no.trim();
+        let mut tokens: Vec<&str> = rest.split_whitespace().collect();
+        let crc = tokens
+            .pop()
+            .and_then(|t| u32::from_str_radix(t, 16).ok())
+            .ok_or_else(|| format!("Line {}: missing checksum", line_no))?;
+        if tokens.is_empty() {
+            total_crc = Some((line_no.to_string(), crc));
+            continue;
+        }
+        let mut bytes = Zeroizing::new(Vec::with_capacity(tokens.len()));
+        for token in &tokens {
+            let byte = u8::from_str_radix(token, 16)
+                .map_err(|_| format!("Line {}: invalid hex", line_no))?;
+            bytes.push(byte);
+        }
+        if crc24(&bytes) != crc {
+            return Err(format!("Line {}: checksum mismatch, check for typos", line_no));
+        }
+        data.extend_from_slice(&bytes);
+    }
+
+    match total_crc {
+        Some((_, crc)) if crc == crc24(&data) => Ok(data),
+        Some((line_no, _)) => Err(format!("Line {}: total checksum mismatch", line_no)),
+        None => Err("Missing total checksum line".to_string()),
+    }
+}
+
+/// Splits backup data into QR-sized chunks of the form
+/// `APT-PAPER <index>/<count> <CRC24> <base64>`.
+pub fn to_qr_chunks(backup: &[u8]) -> Zeroizing<Vec<String>> {
+    use base64::Engine;
+
+    let chunks: Vec<&[u8]> = backup.chunks(QR_CHUNK_SIZE).collect();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut line = format!(
+                "{} {}/{} {:06X} ",
+                QR_CHUNK_PREFIX,
+                i + 1,
+                chunks.len(),
+                crc24(chunk)
+            );
+            line.reserve_exact(chunk.len().div_ceil(3) * 4);
+            base64::engine::general_purpose::STANDARD.encode_string(chunk, &mut line);
+            line
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn from_qr_chunks(text: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    use base64::Engine;
+
+    let mut chunks: Vec<(usize, Zeroizing<Vec<u8>>)> = Vec::new();
+    let mut expected = 0;
+    for line in text.lines().map(str::trim) {
+        let Some(rest) = line.strip_prefix(QR_CHUNK_PREFIX) else {
+            continue;
+        };
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let &[position, crc, data] = fields.as_slice() else {
+            return Err(format!("Malformed QR chunk: {}", line));
+        };
+        let (index, count) = position
+            .split_once('/')
+            .and_then(|(i, c)| Some((i.parse::<usize>().ok()?, c.parse::<usize>().ok()?)))
+            .filter(|&(index, count)| (1..=count).contains(&index))
+            .ok_or_else(|| format!("Malformed QR chunk position: {}", position))?;
+        if expected != 0 && count != expected {
+            return Err(format!(
+                "QR chunk {} belongs to a backup of {} chunks, not {}",
+                index, count, expected
+            ));
+        }
+        if chunks.iter().any(|(i, _)| *i == index) {
+            return Err(format!("QR chunk {} appears more than once", index));
+        }
+        let mut bytes = Zeroizing::new(Vec::with_capacity(data.len() / 4 * 3 + 3));
+        base64::engine::general_purpose::STANDARD
+            .decode_vec(data, &mut bytes)
+            .map_err(|e| format!("QR chunk {}: {}", index, e))?;
+        if u32::from_str_radix(crc, 16).ok() != Some(crc24(&bytes)) {
+            return Err(format!("QR chunk {}: checksum mismatch", index));
+        }
+        expected = count;
+        chunks.push((index, bytes));
+    }
+    if chunks.len() != expected {
+        return Err(format!("Found {} of {} QR chunks", chunks.len(), expected));
+    }
+    chunks.sort_by_key(|(i, _)| *i);
+    let len = chunks.iter().map(|(_, bytes)| bytes.len()).sum();
+    let mut data = Zeroizing::new(Vec::with_capacity(len));
+    for (_, bytes) in &chunks {
+        data.extend_from_slice(bytes);
+    }
+    Ok(data)
+}
+
+/// Builds a printable HTML page with the text backup and its QR chunks.
+pub fn to_html(text: &str, qr_svgs: &[String]) -> String {
+    let codes: String = qr_svgs
+        .iter()
+        .enumerate()
+        .map(|(i, svg)| {
+            format!(
+                "<figure>{}<figcaption>Part {} of {}</figcaption></figure>",
+                svg,
+                i + 1,
+                qr_svgs.len()
+            )
+        })
+        .collect();
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Paper key backup</title>\
+         <style>body{{font-family:sans-serif}}pre{{font-size:11pt}}\
+         figure{{display:inline-block;margin:10px;page-break-inside:avoid}}\
+         svg{{width:250px;height:250px}}</style></head>\
+         <body><h1>Secret key paper backup</h1><pre>{}</pre>{}</body></html>\n",
+        text.replace('<', "&lt;").replace('>', "&gt;"),
+        codes
+    )
+}
+
+fn parse_backup(backup: &[u8]) -> Result<Vec<SecretPart>, String> {
+    let truncated = || "Backup data is truncated".to_string();
+    if backup.first() != Some(&FORMAT_VERSION) {
+        return Err("Unsupported backup format".to_string());
+    }
+    let mut parts = Vec::new();
+    let mut pos = 1;
+    while pos < backup.len() {
+        let fpr_len = backup[pos] as usize;
+        let fingerprint = backup.get(pos + 1..pos + 1 + fpr_len).ok_or_else(truncated)?;
+        pos += 1 + fpr_len;
+        let len = backup.get(pos..pos + 2).ok_or_else(truncated)?;
+        let secret_len = u16::from_be_bytes([len[0], len[1]]) as usize;
+        pos += 2;
+        let secret = backup.get(pos..pos + secret_len).ok_or_else(truncated)?;
+        pos += secret_len;
+        parts.push(SecretPart {
+            fingerprint: fingerprint.to_vec(),
+            secret: Zeroizing::new(secret.to_vec()),
+        });
+    }
+    Ok(parts)
+}
+
+/// Splits binary key data into its packets.
+pub(crate) fn parse_packets(data: &[u8]) -> Result<Vec<Packet<'_>>, String> {
+    let truncated = || "Packet data is truncated".to_string();
+    let mut packets = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let header = data[pos];
+        if header & 0x80 == 0 {
+            return Err(format!("Invalid packet header at offset {}", pos));
+        }
+        pos += 1;
+        let (tag, len) = if header & 0x40 != 0 {
+            let first = *data.get(pos).ok_or_else(truncated)? as usize;
+            let len = match first {
+                0..=191 => {
+                    pos += 1;
+                    first
+                }
+                192..=223 => {
+                    let second = *data.get(pos + 1).ok_or_else(truncated)? as usize;
+                    pos += 2;
+                    ((first - 192) << 8) + second + 192
+                }
+                255 => {
+                    let b = data.get(pos + 1..pos + 5).ok_or_else(truncated)?;
+                    pos += 5;
+                    u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize
+                }
+                _ => return Err("Partial body lengths are not supported in keys".to_string()),
+            };
+            (header & 0x3f, len)
+        } else {
+            let len = match header & 0x03 {
+                0 => {
+                    let b = *data.get(pos).ok_or_else(truncated)?;
+                    pos += 1;
+                    b as usize
+                }
+                1 => {
+                    let b = data.get(pos..pos + 2).ok_or_else(truncated)?;
+                    pos += 2;
+                    u16::from_be_bytes([b[0], b[1]]) as usize
+                }
+                2 => {
+                    let b = data.get(pos..pos + 4).ok_or_else(truncated)?;
+                    pos += 4;
+                    u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize
+                }
+                _ => data.len() - pos,
+            };
+            ((header >> 2) & 0x0f, len)
+        };
+        let body = data.get(pos..pos + len).ok_or_else(truncated)?;
+        packets.push(Packet { tag, body });
+        pos += len;
+    }
+    Ok(packets)
+}
+
+pub(crate) fn write_packet(out: &mut Vec<u8>, tag: u8, body: &[u8]) {
+    out.push(0xc0 | tag);
+    let len = body.len();
+    if len < 192 {
+        out.push(len as u8);
+    } else if len < 8384 {
+        let len = len - 192;
+        out.push(((len >> 8) + 192) as u8);
+        out.push((len & 0xff) as u8);
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(body);
+}
+
+/// Length of the public part of a (public or secret) key packet body.
+pub(crate) fn public_key_len(body: &[u8]) -> Result<usize, String> {
+    let truncated = || "Key packet is truncated".to_string();
+    let version = *body.first().ok_or_else(truncated)?;
+    if version == 6 {
+        // RFC 9580 keys state the length of their key material.
+        let b = body.get(6..10).ok_or_else(truncated)?;
+        let len = 10 + u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize;
+        return if len > body.len() { Err(truncated()) } else { Ok(len) };
+    }
+    if version != 4 {
+        return Err(format!("Version {} keys are not supported", version));
+    }
+    let algorithm = *body.get(5).ok_or_else(truncated)?;
+    let mut pos = 6;
+
+    let mpi = |pos: &mut usize| -> Result<(), String> {
+        let b = body.get(*pos..*pos + 2).ok_or_else(truncated)?;
+        let bits = u16::from_be_bytes([b[0], b[1]]) as usize;
+        *pos += 2 + bits.div_ceil(8);
+        Ok(())
+    };
+    let length_prefixed = |pos: &mut usize| -> Result<(), String> {
+        let len = *body.get(*pos).ok_or_else(truncated)? as usize;
+        *pos += 1 + len;
+        Ok(())
+    };
+
+    match algorithm {
+        // RSA: n, e
+        1..=3 => {
+            mpi(&mut pos)?;
+            mpi(&mut pos)?;
+        }
+        // ElGamal: p, g, y
+        16 | 20 => {
+            for _ in 0..3 {
+                mpi(&mut pos)?;
+            }
+        }
+        // DSA: p, q, g, y
+        17 => {
+            for _ in 0..4 {
+                mpi(&mut pos)?;
+            }
+        }
+        // ECDH: curve OID, point, KDF parameters
+        18 => {
+            length_prefixed(&mut pos)?;
+            mpi(&mut pos)?;
+            length_prefixed(&mut pos)?;
+        }
+        // ECDSA, EdDSA: curve OID, point
+        19 | 22 => {
+            length_prefixed(&mut pos)?;
+            mpi(&mut pos)?;
+        }
+        _ => return Err(format!("Unsupported public key algorithm {}", algorithm)),
+    }
+
+    if pos > body.len() {
+        return Err(truncated());
+    }
+    Ok(pos)
+}
+
+/// Fingerprint of a public key packet body: SHA-1 for v4 keys and SHA-256
+/// for v6 keys (RFC 9580, section 5.5.4).
+pub(crate) fn fingerprint(public_body: &[u8]) -> Result<Vec<u8>, String> {
+    if public_body.first() == Some(&6) {
+        let len = u32::try_from(public_body.len()).map_err(|_| "Key packet too large")?;
+        let mut hasher = Sha256::new();
+        hasher.update([0x9b]);
+        hasher.update(len.to_be_bytes());
+        hasher.update(public_body);
+        return Ok(hasher.finalize().to_vec());
+    }
+    let len = u16::try_from(public_body.len()).map_err(|_| "Key packet too large")?;
+    let mut hasher = Sha1::new();
+    hasher.update([0x99]);
+    hasher.update(len.to_be_bytes());
+    hasher.update(public_body);
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Key ID of a fingerprint: the first 8 bytes of a v6 fingerprint and the
+/// last 8 of a v4 one.
+pub(crate) fn key_id(fingerprint: &[u8]) -> &[u8] {
+    if fingerprint.len() == 32 {
+        &fingerprint[..8]
+    } else {
+        &fingerprint[fingerprint.len().saturating_sub(8)..]
+    }
+}
+
+/// Public parts of the key packets in binary key data, with their
+/// fingerprints.
+pub(crate) fn public_keys(data: &[u8]) -> Result<Vec<(Vec<u8>, &[u8])>, String> {
+    let mut keys = Vec::new();
+    for packet in parse_packets(data)? {
+        if matches!(
+            packet.tag,
+            TAG_SECRET_KEY | TAG_PUBLIC_KEY | TAG_SECRET_SUBKEY | TAG_PUBLIC_SUBKEY
+        ) {
+            let public = &packet.body[..public_key_len(packet.body)?];
+            keys.push((fingerprint(public)?, public));
+        }
+    }
+    Ok(keys)
+}
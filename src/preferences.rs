@@ -0,0 +1,219 @@
+//! Picks the algorithms for a message from the preference subpackets of all
+//! its recipients' keys (RFC 4880, section 13.2, and RFC 9580, section
+//! 5.2.3.15).
+
+use std::fmt;
+
+use pgp::native::crypto::{hash::HashAlgorithm, sym::SymmetricKeyAlgorithm};
+
+use crate::{audit, compression::Compression, packets};
+
+/// Ciphers this tool encrypts with.
+const CIPHERS: [SymmetricKeyAlgorithm; 7] = [
+    SymmetricKeyAlgorithm::AES256,
+    SymmetricKeyAlgorithm::AES192,
+    SymmetricKeyAlgorithm::AES128,
+    SymmetricKeyAlgorithm::Twofish,
+    SymmetricKeyAlgorithm::CAST5,
+    SymmetricKeyAlgorithm::Blowfish,
+    SymmetricKeyAlgorithm::TripleDES,
+];
+const LEGACY_CIPHERS: [SymmetricKeyAlgorithm; 4] = [
+    SymmetricKeyAlgorithm::IDEA,
+    SymmetricKeyAlgorithm::TripleDES,
+    SymmetricKeyAlgorithm::CAST5,
+    SymmetricKeyAlgorithm::Blowfish,
+];
+/// Hashes this tool signs with.
+const HASHES: [HashAlgorithm; 5] = [
+    HashAlgorithm::SHA2_512,
+    HashAlgorithm::SHA2_384,
+    HashAlgorithm::SHA2_256,
+    HashAlgorithm::SHA2_224,
+    HashAlgorithm::SHA1,
+];
+const LEGACY_HASHES: [HashAlgorithm; 3] = [
+    HashAlgorithm::MD5,
+    HashAlgorithm::SHA1,
+    HashAlgorithm::RIPEMD160,
+];
+
+/// Features subpacket flag for SEIPD v2 (RFC 9580, section 5.2.3.32).
+const FEATURE_SEIPD_V2: u8 = 0x08;
+/// AEAD modes in the order this tool prefers them. OCB is the one every
+/// RFC 9580 implementation accepts.
+const AEAD_MODES: [AeadMode; 3] = [AeadMode::Ocb, AeadMode::Gcm, AeadMode::Eax];
+
+/// AEAD mode of an RFC 9580 SEIPD v2 packet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AeadMode {
+    Eax,
+    Ocb,
+    Gcm,
+}
+
+impl AeadMode {
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(AeadMode::Eax),
+            2 => Some(AeadMode::Ocb),
+            3 => Some(AeadMode::Gcm),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AeadMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AeadMode::Eax => "EAX",
+            AeadMode::Ocb => "OCB",
+            AeadMode::Gcm => "GCM",
+        })
+    }
+}
+
+/// Algorithms every recipient accepts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Negotiated {
+    pub cipher: SymmetricKeyAlgorithm,
+    /// Hash every recipient accepts for signatures. Encrypting does not
+    /// sign, so this is not part of [`Negotiated::summary`].
+    pub hash: HashAlgorithm,
+    pub compression: Compression,
+    /// AEAD mode every recipient accepts, `None` unless all of them
+    /// announce SEIPD v2 support. Messages are written as SEIPD v2 with it,
+    /// and as SEIPD v1 with MDC without it.
+    pub aead: Option<AeadMode>,
+    /// Recipients that only accept legacy algorithms.
+    pub warnings: Vec<String>,
+}
+
+impl Negotiated {
+    /// The algorithms a message is encrypted with, e.g. "AES-256, ZLIB".
+    pub fn summary(&self) -> String {
+        format!(
+            "{}, {}",
+            packets::symmetric_algorithm(self.cipher as u8),
+            packets::compression_algorithm(self.compression.id())
+        )
+    }
+}
+
+/// Algorithm IDs in the order a key prefers them.
+#[derive(Default)]
+struct Preferences {
+    ciphers: Vec<u8>,
+    hashes: Vec<u8>,
+    compression: Vec<u8>,
+    seipd_v2: bool,
+    aead: Vec<AeadMode>,
+}
+
+/// Negotiates the algorithms for a message to the binary public keys in
+/// `keys`. For each kind, the first algorithm of the first recipient that
+/// every recipient accepts is used. TripleDES, SHA-1 and no compression are
+/// accepted by everyone even when not listed.
+pub fn negotiate(keys: &[Vec<u8>]) -> Negotiated {
+    let preferences: Vec<(String, Preferences)> =
+        keys.iter().map(|key| preferences(key)).collect();
+
+    let mut warnings = Vec::new();
+    for (name, prefs) in &preferences {
+        if prefs.ciphers.iter().all(|c| LEGACY_CIPHERS.iter().any(|l| *l as u8 == *c)) {
+            warnings.push(format!("{} only accepts legacy ciphers such as TripleDES", name));
+        }
+        if prefs.hashes.iter().all(|h| LEGACY_HASHES.iter().any(|l| *l as u8 == *h)) {
+            warnings.push(format!("{} only accepts legacy hashes such as SHA-1", name));
+        }
+    }
+
+    let cipher = pick(
+        preferences.iter().map(|(_, p)| &p.ciphers),
+        &CIPHERS.map(|c| c as u8),
+        SymmetricKeyAlgorithm::TripleDES as u8,
+    );
+    let hash = pick(
+        preferences.iter().map(|(_, p)| &p.hashes),
+        &HASHES.map(|h| h as u8),
+        HashAlgorithm::SHA1 as u8,
+    );
+    let compression = pick(
+        preferences.iter().map(|(_, p)| &p.compression),
+        &Compression::ALL.map(Compression::id),
+        Compression::None.id(),
+    );
+    Negotiated {
+        cipher: CIPHERS
+            .into_iter()
+            .find(|c| *c as u8 == cipher)
+            .unwrap_or(SymmetricKeyAlgorithm::TripleDES),
+        hash: HASHES.into_iter().find(|h| *h as u8 == hash).unwrap_or(HashAlgorithm::SHA1),
+        compression: Compression::from_id(compression).unwrap_or(Compression::None),
+        aead: preferences.iter().all(|(_, p)| p.seipd_v2).then(|| {
+            pick(preferences.iter().map(|(_, p)| &p.aead), &AEAD_MODES, AeadMode::Ocb)
+        }),
+        warnings,
+    }
+}
+
+/// The name of a key and its preferences. Each list comes from the newest
+/// self-signature of the primary user ID or, when that does not state it,
+/// from the newest direct-key signature. Keys without compression
+/// preferences prefer ZIP (RFC 4880, section 5.2.3.9), and v6 keys accept
+/// SEIPD v2 even when their features do not say so.
+fn preferences(key: &[u8]) -> (String, Preferences) {
+    let Ok(key) = audit::preferences(key) else {
+        return (String::from("A recipient"), Preferences::default());
+    };
+    let subpacket = |kind: u8| {
+        [&key.user_id, &key.direct_key]
+            .into_iter()
+            .find_map(|subpackets| subpackets.iter().find(|(k, _)| *k == kind))
+            .map(|(_, data)| data.as_slice())
+    };
+
+    let mut prefs = Preferences {
+        ciphers: subpacket(11).unwrap_or_default().to_vec(),
+        hashes: subpacket(21).unwrap_or_default().to_vec(),
+        compression: subpacket(22).unwrap_or_default().to_vec(),
+        seipd_v2: key.rfc9580
+            || subpacket(30)
+                .and_then(<[u8]>::first)
+                .is_some_and(|flags| flags & FEATURE_SEIPD_V2 != 0),
+        aead: Vec::new(),
+    };
+    if prefs.compression.is_empty() {
+        prefs.compression.push(Compression::Zip.id());
+    }
+    // The preferred AEAD ciphersuites of RFC 9580, then the preferred AEAD
+    // algorithms of earlier drafts.
+    let suites = subpacket(39).unwrap_or_default().chunks_exact(2).map(|suite| suite[1]);
+    let algorithms = subpacket(34).unwrap_or_default().iter().copied();
+    for mode in suites.chain(algorithms).filter_map(AeadMode::from_id) {
+        if !prefs.aead.contains(&mode) {
+            prefs.aead.push(mode);
+        }
+    }
+    (key.name, prefs)
+}
+
+/// First algorithm in the first list that every list accepts and that is
+/// `supported`, or else `implicit`, which every implementation accepts.
+fn pick<'a, T: Copy + PartialEq + 'a>(
+    lists: impl Iterator<Item = &'a Vec<T>> + Clone,
+    supported: &[T],
+    implicit: T,
+) -> T {
+    let accepted = |alg: &T| lists.clone().all(|list| *alg == implicit || list.contains(alg));
+    lists
+        .clone()
+        .next()
+        .and_then(|first| {
+            first
+                .iter()
+                .copied()
+                .find(|alg| supported.contains(alg) && accepted(alg))
+        })
+        .unwrap_or(implicit)
+}
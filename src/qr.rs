@@ -0,0 +1,42 @@
+//! QR code rendering for display and PNG export, and decoding of QR codes
+//! from image files.
+
+use std::io::Cursor;
+
+use image::{ImageFormat, Luma};
+use qrcode::{render::svg, types::QrError, QrCode};
+
+/// Renders `data` as an SVG QR code.
+pub fn to_svg(data: &str) -> Result<String, QrError> {
+    let code = QrCode::new(data.as_bytes())?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .dark_color(svg::Color("#000000"))
+        .light_color(svg::Color("#ffffff"))
+        .build())
+}
+
+/// Renders `data` as a PNG image, large enough to be printed.
+pub fn to_png(data: &str) -> Result<Vec<u8>, String> {
+    let code = QrCode::new(data.as_bytes()).map_err(|e| e.to_string())?;
+    let img = code.render::<Luma<u8>>().min_dimensions(600, 600).build();
+
+    let mut png = Vec::new();
+    image::DynamicImage::ImageLuma8(img)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+/// Decodes the first QR code found in an image file.
+pub fn decode_image(bytes: &[u8]) -> Result<String, String> {
+    let img = image::load_from_memory(bytes)
+        .map_err(|e| e.to_string())?
+        .to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare(img);
+    let grids = prepared.detect_grids();
+    let grid = grids.first().ok_or("No QR code found in image")?;
+    let (_, content) = grid.decode().map_err(|e| e.to_string())?;
+    Ok(content)
+}
@@ -0,0 +1,369 @@
+//! RFC 9580 (OpenPGP v6) keys, signatures and SEIPD v2 messages, including
+//! passphrase-only ones with Argon2 S2K. They go through a newer version of
+//! the OpenPGP library than the rest of the tool, as the one bundled with
+//! pgp-lib only handles v4 keys and SEIPD v1. Keys and messages cross
+//! between the two versions in binary form.
+
+use rpgp::{
+    crypto::{
+        aead::{AeadAlgorithm, ChunkSize},
+        hash::HashAlgorithm,
+        sym::SymmetricKeyAlgorithm,
+    },
+    errors::Error as PgpError,
+    packet::{PublicKey, PublicSubkey, Signature, UserAttribute, UserId},
+    ser::Serialize,
+    types::{CompressionAlgorithm, KeyVersion, StringToKey, Tag, Version},
+    decrypt_session_key_with_password, Deserializable, Esk, KeyType, Message,
+    SecretKeyParamsBuilder, SignedPublicKey, SignedSecretKey, StandaloneSignature,
+    SubkeyParamsBuilder,
+};
+use zeroize::Zeroizing;
+
+use crate::audit::Signed;
+use crate::preferences::AeadMode;
+use crate::{paperkey, symmetric, Error, Field, Result};
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_PUBLIC_KEY: u8 = 6;
+
+/// Argon2 passes, lanes and memory (2^16 KiB) of the second recommended
+/// parameter set of RFC 9580, section 3.7.1.4, for machines that cannot
+/// spare the 2 GiB of the first.
+const ARGON2_PASSES: u8 = 3;
+const ARGON2_PARALLELISM: u8 = 4;
+const ARGON2_MEMORY_EXPONENT: u8 = 16;
+
+/// Whether binary keys or signatures are RFC 9580 (v6) ones.
+pub(crate) fn is_rfc9580(data: &[u8]) -> bool {
+    paperkey::parse_packets(data).is_ok_and(|packets| {
+        packets.iter().any(|packet| {
+            matches!(packet.tag, TAG_SIGNATURE | TAG_SECRET_KEY | TAG_PUBLIC_KEY)
+                && packet.body.first() == Some(&6)
+        })
+    })
+}
+
+/// Generates a v6 key with an Ed25519 primary key that certifies and signs
+/// and an X25519 subkey that encrypts, and returns the binary secret and
+/// public keys. Like the v4 keys, it has an empty user ID and no
+/// passphrase.
+pub(crate) fn generate_keypair() -> Result<(Zeroizing<Vec<u8>>, Vec<u8>)> {
+    let subkey = SubkeyParamsBuilder::default()
+        .version(KeyVersion::V6)
+        .key_type(KeyType::X25519)
+        .can_encrypt(true)
+        .build()
+        .map_err(|e| Error::GenerateKeys(e.to_string()))?;
+    let params = SecretKeyParamsBuilder::default()
+        .version(KeyVersion::V6)
+        .key_type(KeyType::Ed25519)
+        .can_certify(true)
+        .can_sign(true)
+        .primary_user_id(String::new())
+        .preferred_symmetric_algorithms(
+            vec![SymmetricKeyAlgorithm::AES256, SymmetricKeyAlgorithm::AES128].into(),
+        )
+        .preferred_hash_algorithms(vec![HashAlgorithm::SHA2_512, HashAlgorithm::SHA2_256].into())
+        .preferred_compression_algorithms(
+            vec![CompressionAlgorithm::ZLIB, CompressionAlgorithm::ZIP].into(),
+        )
+        .preferred_aead_algorithms(
+            vec![
+                (SymmetricKeyAlgorithm::AES256, AeadAlgorithm::Ocb),
+                (SymmetricKeyAlgorithm::AES256, AeadAlgorithm::Gcm),
+                (SymmetricKeyAlgorithm::AES128, AeadAlgorithm::Ocb),
+            ]
+            .into(),
+        )
+        .subkey(subkey)
+        .build()
+        .map_err(|e| Error::GenerateKeys(e.to_string()))?;
+
+    let classify = |e: PgpError| Error::classify(Field::SecretKey, &e, Error::GenerateKeys);
+    let mut rng = rand::thread_rng();
+    let secret_key = params
+        .generate(&mut rng)
+        .and_then(|key| key.sign(&mut rng, String::new))
+        .map_err(classify)?;
+    let public_key = secret_key
+        .public_key()
+        .sign(&mut rng, &secret_key, String::new)
+        .map_err(classify)?;
+    Ok((
+        secret_key.to_bytes().map(Zeroizing::new).map_err(classify)?,
+        public_key.to_bytes().map_err(classify)?,
+    ))
+}
+
+/// Checks that binary data holds a v6 public key the library can read.
+pub(crate) fn check_public_key(data: &[u8]) -> Result<()> {
+    read_signed_public_key(data).map(|_| ())
+}
+
+/// Checks that binary data holds a v6 secret key the library can read.
+pub(crate) fn check_secret_key(data: &[u8]) -> Result<()> {
+    read_signed_secret_key(data).map(|_| ())
+}
+
+fn read_signed_public_key(data: &[u8]) -> Result<SignedPublicKey> {
+    SignedPublicKey::from_bytes(data).map_err(|e| {
+        Error::classify(Field::PublicKey, &e, |detail| {
+            Error::MalformedArmor(Field::PublicKey, detail)
+        })
+    })
+}
+
+fn read_signed_secret_key(data: &[u8]) -> Result<SignedSecretKey> {
+    SignedSecretKey::from_bytes(data).map_err(|e| {
+        Error::classify(Field::SecretKey, &e, |detail| {
+            Error::MalformedArmor(Field::SecretKey, detail)
+        })
+    })
+}
+
+/// Encrypts `message`, a binary literal data packet or a compressed packet
+/// holding one, as SEIPD v2 to the public key packet bodies in `keys`.
+pub(crate) fn encrypt(
+    keys: &[&[u8]],
+    message: &[u8],
+    cipher: symmetric::SymmetricKeyAlgorithm,
+    aead: AeadMode,
+) -> Result<Vec<u8>> {
+    let classify = |e: PgpError| Error::classify(Field::PublicKey, &e, Error::Encrypt);
+    // A primary key is read as a subkey, so one message can go to both.
+    let keys = keys
+        .iter()
+        .map(|body| PublicSubkey::from_slice(Version::New, body))
+        .collect::<rpgp::errors::Result<Vec<_>>>()
+        .map_err(classify)?;
+    let keys: Vec<&PublicSubkey> = keys.iter().collect();
+    let mut rng = rand::thread_rng();
+    Message::from_bytes(message)
+        .and_then(|msg| {
+            msg.encrypt_to_keys_seipdv2(
+                &mut rng,
+                aead_cipher(cipher),
+                aead_algorithm(aead),
+                ChunkSize::default(),
+                &keys[..],
+            )
+        })
+        .and_then(|msg| msg.to_bytes())
+        .map_err(classify)
+}
+
+/// Encrypts `message` like [`encrypt`], with a passphrase instead of keys:
+/// the session key goes into a v6 SKESK packet with Argon2 S2K and the
+/// data is sealed with OCB.
+pub(crate) fn encrypt_with_password(
+    passphrase: &str,
+    message: &[u8],
+    cipher: symmetric::SymmetricKeyAlgorithm,
+) -> Result<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    let s2k = StringToKey::new_argon2(
+        &mut rng,
+        ARGON2_PASSES,
+        ARGON2_PARALLELISM,
+        ARGON2_MEMORY_EXPONENT,
+    );
+    let passphrase = passphrase.to_string();
+    Message::from_bytes(message)
+        .and_then(|msg| {
+            msg.encrypt_with_password_seipdv2(
+                &mut rng,
+                s2k,
+                aead_cipher(cipher),
+                AeadAlgorithm::Ocb,
+                ChunkSize::default(),
+                || passphrase,
+            )
+        })
+        .and_then(|msg| msg.to_bytes())
+        .map_err(|e| Error::Encrypt(e.to_string()))
+}
+
+/// The cipher of a SEIPD v2 message, which needs 16-byte blocks. AES-128
+/// stands in for the others, as every RFC 9580 implementation accepts it.
+fn aead_cipher(cipher: symmetric::SymmetricKeyAlgorithm) -> SymmetricKeyAlgorithm {
+    match cipher {
+        symmetric::SymmetricKeyAlgorithm::AES256 => SymmetricKeyAlgorithm::AES256,
+        symmetric::SymmetricKeyAlgorithm::AES192 => SymmetricKeyAlgorithm::AES192,
+        _ => SymmetricKeyAlgorithm::AES128,
+    }
+}
+
+fn aead_algorithm(mode: AeadMode) -> AeadAlgorithm {
+    match mode {
+        AeadMode::Eax => AeadAlgorithm::Eax,
+        AeadMode::Ocb => AeadAlgorithm::Ocb,
+        AeadMode::Gcm => AeadAlgorithm::Gcm,
+    }
+}
+
+/// Decrypts a binary message with a binary secret key and returns the
+/// message inside: a literal data packet or a compressed packet holding
+/// one. SEIPD v1 messages to v6 keys are read as well.
+pub(crate) fn decrypt(
+    secret_key: &[u8],
+    passphrase: &str,
+    message: &[u8],
+) -> Result<Zeroizing<Vec<u8>>> {
+    let skey = read_signed_secret_key(secret_key)?;
+    let classify = |e: PgpError| Error::classify(Field::EncryptedMessage, &e, Error::Decrypt);
+    let passphrase = passphrase.to_string();
+    let (msg, _) = Message::from_bytes(message)
+        .and_then(|msg| msg.decrypt(|| passphrase, &[&skey]))
+        .map_err(classify)?;
+    msg.to_bytes().map(Zeroizing::new).map_err(classify)
+}
+
+/// Decrypts a binary message with a passphrase and returns the message
+/// inside, like [`decrypt`]. Only a SKESK packet that does not open means
+/// the passphrase is wrong; a failed AEAD check of the data is reported as
+/// a damaged message.
+pub(crate) fn decrypt_with_password(
+    passphrase: &str,
+    message: &[u8],
+) -> Result<Zeroizing<Vec<u8>>> {
+    let not_symmetric = || Error::Decrypt("The message is not encrypted with a passphrase".into());
+    let msg = Message::from_bytes(message)
+        .map_err(|e| Error::MalformedArmor(Field::EncryptedMessage, e.to_string()))?;
+    let Message::Encrypted { esk, edata } = msg else {
+        return Err(not_symmetric());
+    };
+    let skesk = esk
+        .iter()
+        .find_map(|esk| match esk {
+            Esk::SymKeyEncryptedSessionKey(skesk) => Some(skesk),
+            _ => None,
+        })
+        .ok_or_else(not_symmetric)?;
+    let passphrase = passphrase.to_string();
+    let session_key = decrypt_session_key_with_password(skesk, || passphrase)
+        .map_err(|_| Error::WrongPassphrase)?;
+    edata
+        .decrypt(session_key)
+        .and_then(|msg| msg.to_bytes())
+        .map(Zeroizing::new)
+        .map_err(|e| Error::Decrypt(e.to_string()))
+}
+
+/// Signs `data` as binary with the primary key of a binary v6 secret key
+/// and returns the signature packet.
+pub(crate) fn sign(secret_key: &[u8], passphrase: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let skey = read_signed_secret_key(secret_key)?;
+    let classify = |e: PgpError| Error::classify(Field::SecretKey, &e, Error::Sign);
+    let passphrase = passphrase.to_string();
+    let mut rng = rand::thread_rng();
+    let signed = Message::new_literal_bytes("", data)
+        .sign(&mut rng, &skey, || passphrase, HashAlgorithm::SHA2_256)
+        .map_err(classify)?;
+    let Message::Signed { signature, .. } = signed else {
+        return Err(Error::Sign("The message was not signed".to_string()));
+    };
+    StandaloneSignature::new(signature).to_bytes().map_err(classify)
+}
+
+/// Signs `message`, a binary literal data packet, with the primary key of a
+/// binary v6 secret key and returns the inline signed message: a one-pass
+/// signature packet, the literal data packet and the signature packet.
+pub(crate) fn sign_message(
+    secret_key: &[u8],
+    passphrase: &str,
+    message: &[u8],
+) -> Result<Vec<u8>> {
+    let skey = read_signed_secret_key(secret_key)?;
+    let passphrase = passphrase.to_string();
+    let mut rng = rand::thread_rng();
+    Message::from_bytes(message)
+        .and_then(|msg| msg.sign(&mut rng, &skey, || passphrase, HashAlgorithm::SHA2_256))
+        .and_then(|msg| msg.to_bytes())
+        .map_err(|e| Error::classify(Field::SecretKey, &e, Error::Sign))
+}
+
+/// Checks a binary signature over `data` against the primary key of a
+/// binary public key, or else against its subkeys that are bound to it.
+pub(crate) fn verify(public_key: &[u8], signature: &[u8], data: &[u8]) -> Result<()> {
+    let pkey = read_signed_public_key(public_key)?;
+    let sig = StandaloneSignature::from_bytes(signature).map_err(malformed_signed_message)?;
+    check_signer(&pkey, |key| sig.verify(key, data), |subkey| sig.verify(subkey, data))
+}
+
+/// Checks a binary inline signed message, already decompressed, like
+/// [`verify`] and returns the literal data packet inside.
+pub(crate) fn verify_message(public_key: &[u8], message: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let pkey = read_signed_public_key(public_key)?;
+    let msg = Message::from_bytes(message).map_err(malformed_signed_message)?;
+    let Message::Signed {
+        message: Some(inner),
+        ..
+    } = &msg
+    else {
+        return Err(Error::InvalidSignedMessage("not an inline signed message"));
+    };
+    check_signer(&pkey, |key| msg.verify(key), |subkey| msg.verify(subkey))?;
+    inner
+        .to_bytes()
+        .map(Zeroizing::new)
+        .map_err(malformed_signed_message)
+}
+
+/// Runs `verify` with the primary key of `pkey`, or else `verify_subkey`
+/// with its subkeys that are bound to it, and reports the primary key's
+/// failure.
+fn check_signer(
+    pkey: &SignedPublicKey,
+    verify: impl FnOnce(&SignedPublicKey) -> rpgp::errors::Result<()>,
+    verify_subkey: impl Fn(&PublicSubkey) -> rpgp::errors::Result<()>,
+) -> Result<()> {
+    let result = verify(pkey);
+    if result.is_ok()
+        || pkey
+            .public_subkeys
+            .iter()
+            .any(|subkey| subkey.verify(pkey).is_ok() && verify_subkey(&subkey.key).is_ok())
+    {
+        return Ok(());
+    }
+    result.map_err(|e| Error::classify(Field::PublicKey, &e, Error::BadSignature))
+}
+
+fn malformed_signed_message(e: PgpError) -> Error {
+    Error::classify(Field::SignedMessage, &e, |detail| {
+        Error::MalformedArmor(Field::SignedMessage, detail)
+    })
+}
+
+/// Reads the public part of a v6 key packet body, `None` for algorithms
+/// the library does not support.
+pub(crate) fn read_public_key(body: &[u8]) -> Option<PublicKey> {
+    let public_len = paperkey::public_key_len(body).ok()?;
+    PublicKey::from_slice(Version::New, &body[..public_len]).ok()
+}
+
+/// Whether the v6 signature packet `body` is a valid signature by the
+/// primary key packet body `primary` over what `signed` names.
+pub(crate) fn verify_self_signature(primary: &[u8], signed: &Signed, body: &[u8]) -> bool {
+    let (Some(primary), Ok(sig)) =
+        (read_public_key(primary), Signature::from_slice(Version::New, body))
+    else {
+        return false;
+    };
+    match signed {
+        Signed::Key => sig.verify_key(&primary),
+        Signed::UserId(id) => UserId::from_slice(Version::New, id)
+            .and_then(|id| sig.verify_certification(&primary, Tag::UserId, &id)),
+        Signed::UserAttribute(attribute) => UserAttribute::from_slice(Version::New, attribute)
+            .and_then(|attribute| {
+                sig.verify_certification(&primary, Tag::UserAttribute, &attribute)
+            }),
+        Signed::Subkey(subkey) => match read_public_key(subkey) {
+            Some(subkey) => sig.verify_key_binding(&primary, &subkey),
+            None => return false,
+        },
+    }
+    .is_ok()
+}
@@ -0,0 +1,142 @@
+//! Per-session webview data directory.
+//!
+//! Every launch gets its own randomly named directory below
+//! `<temp>/another_pgp_tool`. A lock file inside it marks the directory as
+//! in use for as long as the process runs; the directory is locked before
+//! it is moved there, so another instance starting up never mistakes it for
+//! a stale one. On exit the directory is overwritten and removed;
+//! directories left behind by a crash are no longer locked and are wiped on
+//! the next launch. Problems are reported through [`leftovers`] for the UI
+//! to show, as the desktop build has no console.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const SESSION_PREFIX: &str = "session-";
+const LOCK_FILE: &str = ".lock";
+
+static LEFTOVERS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+/// The data directory of the running session. The lock file is held open
+/// until the process exits.
+pub struct SessionDir {
+    path: PathBuf,
+    _lock: File,
+}
+
+impl SessionDir {
+    /// Wipes directories of earlier sessions and creates a fresh one for
+    /// this session.
+    pub fn create() -> io::Result<Self> {
+        let root = std::env::temp_dir().join("another_pgp_tool");
+        fs::create_dir_all(&root)?;
+
+        let _ = LEFTOVERS.set(recover(&root));
+
+        let mut id = [0u8; 16];
+        getrandom::getrandom(&mut id).map_err(io::Error::other)?;
+        let name: String = id.iter().map(|b| format!("{:02x}", b)).collect();
+        let pending = std::env::temp_dir().join(format!("another_pgp_tool-pending-{}", name));
+        fs::create_dir(&pending)?;
+        let lock = File::create(pending.join(LOCK_FILE))?;
+        lock.try_lock().map_err(io::Error::other)?;
+
+        let path = root.join(format!("{}{}", SESSION_PREFIX, name));
+        if let Err(e) = fs::rename(&pending, &path) {
+            drop(lock);
+            let _ = wipe(&pending);
+            return Err(e);
+        }
+        Ok(Self { path, _lock: lock })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Overwrites and removes the session directory. Files still held open
+    /// by the webview may survive; they are wiped on the next launch, or
+    /// reported there through [`leftovers`] if that fails too.
+    pub fn wipe(&self) -> io::Result<()> {
+        wipe(&self.path)
+    }
+}
+
+/// Directories from earlier sessions that were still present after the
+/// startup wipe, e.g. because another instance is using them.
+pub fn leftovers() -> &'static [PathBuf] {
+    LEFTOVERS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Wipes everything below `root` that does not belong to a running session
+/// and returns the directories that could not be removed.
+fn recover(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut leftovers = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_session = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(SESSION_PREFIX));
+        if is_session && is_locked(&path) {
+            // Another instance is running with this directory.
+            leftovers.push(path);
+            continue;
+        }
+        // Stale session directories and data from versions that used
+        // `<temp>/another_pgp_tool` directly.
+        if wipe(&path).is_err() {
+            leftovers.push(path);
+        }
+    }
+    leftovers
+}
+
+fn is_locked(dir: &Path) -> bool {
+    let Ok(file) = OpenOptions::new().write(true).open(dir.join(LOCK_FILE)) else {
+        return false;
+    };
+    match file.try_lock() {
+        Ok(()) => {
+            let _ = file.unlock();
+            false
+        }
+        Err(_) => true,
+    }
+}
+
+/// Overwrites every file below `path` with zeros before removing it. Flash
+/// storage and copy-on-write file systems may still keep the old blocks,
+/// so this is a best effort on top of removing the data.
+fn wipe(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            // Keep going so as much as possible is overwritten.
+            let _ = wipe(&entry?.path());
+        }
+        fs::remove_dir(path)
+    } else {
+        if metadata.is_file() {
+            overwrite(path, metadata.len())?;
+        }
+        fs::remove_file(path)
+    }
+}
+
+fn overwrite(path: &Path, len: u64) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; 8192];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..n])?;
+        remaining -= n as u64;
+    }
+    file.sync_all()
+}
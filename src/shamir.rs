@@ -0,0 +1,142 @@
+//! m-of-n Shamir secret sharing of secret keys and passphrases. Every share
+//! is exported as its own armored block so it can be handed out as text.
+//! The shared data ends in a truncated SHA-256 of the secret, so a wrong
+//! combination of shares is reported instead of returning garbage.
+
+use sha2::{Digest, Sha256};
+use sharks::{Share, Sharks};
+use zeroize::Zeroizing;
+
+use crate::armor;
+
+const SHARE_LABEL: &str = "ANOTHER PGP TOOL SECRET SHARE";
+/// Length of the checksum appended to the secret before splitting.
+const CHECKSUM_LEN: usize = 4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SecretKind {
+    SecretKey,
+    Passphrase,
+}
+
+impl SecretKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SecretKind::SecretKey => "secret-key",
+            SecretKind::Passphrase => "passphrase",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "secret-key" => Some(SecretKind::SecretKey),
+            "passphrase" => Some(SecretKind::Passphrase),
+            _ => None,
+        }
+    }
+}
+
+/// Splits `secret` into `count` armored shares, any `threshold` of which
+/// recover it.
+pub fn split(
+    secret: &[u8],
+    kind: SecretKind,
+    threshold: u8,
+    count: u8,
+) -> Result<Vec<String>, String> {
+    if threshold < 2 || threshold > count {
+        return Err("Threshold must be at least 2 and at most the number of shares".to_string());
+    }
+    if secret.is_empty() {
+        return Err("Nothing to split".to_string());
+    }
+
+    let mut set_id = [0u8; 4];
+    getrandom::getrandom(&mut set_id).map_err(|e| e.to_string())?;
+    let set_id: String = set_id.iter().map(|b| format!("{:02X}", b)).collect();
+
+    let mut data = Zeroizing::new(secret.to_vec());
+    data.extend_from_slice(&checksum(secret));
+    Ok(Sharks(threshold)
+        .dealer(&data)
+        .take(count as usize)
+        .enumerate()
+        .map(|(i, share)| {
+            let headers = vec![
+                ("Comment".to_string(), format!("Share {} of {}", i + 1, count)),
+                ("Content".to_string(), kind.as_str().to_string()),
+                ("Threshold".to_string(), threshold.to_string()),
+                ("Set".to_string(), set_id.clone()),
+            ];
+            armor::encode(SHARE_LABEL, &headers, &Vec::from(&share))
+        })
+        .collect())
+}
+
+/// Recombines the armored shares found in `text`. All shares must come
+/// from the same split, and the recovered secret must match its checksum.
+pub fn combine(text: &str) -> Result<(SecretKind, Zeroizing<Vec<u8>>), String> {
+    let blocks: Vec<armor::Block> = armor::decode_all(text)?
+        .into_iter()
+        .filter(|b| b.label == SHARE_LABEL)
+        .collect();
+    let first = blocks.first().ok_or("No shares found")?;
+
+    let header = |block: &armor::Block, key: &str| {
+        block
+            .headers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default()
+    };
+    let kind = SecretKind::parse(&header(first, "Content")).ok_or("Unknown share content")?;
+    let threshold: u8 = header(first, "Threshold")
+        .parse()
+        .map_err(|_| "Invalid share threshold")?;
+
+    let mut shares: Vec<Share> = Vec::new();
+    for block in &blocks {
+        let same_split = ["Set", "Threshold", "Content"]
+            .iter()
+            .all(|key| header(block, key) == header(first, key));
+        if !same_split {
+            return Err("Shares come from different splits".to_string());
+        }
+        let share = Share::try_from(block.data.as_slice()).map_err(|e| e.to_string())?;
+        if !shares.iter().any(|s| Vec::from(s)[0] == block.data[0]) {
+            shares.push(share);
+        }
+    }
+    if shares.len() < threshold as usize {
+        return Err(format!(
+            "{} of {} required shares provided",
+            shares.len(),
+            threshold
+        ));
+    }
+
+    let mut secret = Sharks(threshold)
+        .recover(&shares)
+        .map(Zeroizing::new)
+        .map_err(|e| e.to_string())?;
+    let split_at = secret
+        .len()
+        .checked_sub(CHECKSUM_LEN)
+        .ok_or("The recovered secret is too short")?;
+    if secret[split_at..] != checksum(&secret[..split_at]) {
+        return Err("The shares do not combine to the original secret, check that they \
+                    belong together and are not damaged"
+            .to_string());
+    }
+    secret.truncate(split_at);
+    Ok((kind, secret))
+}
+
+/// The first bytes of the SHA-256 digest of `secret`.
+fn checksum(secret: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = Sha256::digest(secret);
+    let mut out = [0; CHECKSUM_LEN];
+    out.copy_from_slice(&digest[..CHECKSUM_LEN]);
+    out
+}
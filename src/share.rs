@@ -0,0 +1,54 @@
+//! Text shared to the app from other apps. On Android this is the
+//! `ACTION_SEND` intent that started the activity, delivered through the
+//! share sheet.
+
+#[cfg(target_os = "android")]
+pub fn received_text() -> Option<String> {
+    use jni::objects::{JObject, JString};
+
+    let ctx = ndk_context::android_context();
+    let vm = unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) }.ok()?;
+    let mut env = vm.attach_current_thread().ok()?;
+    let activity = unsafe { JObject::from_raw(ctx.context().cast()) };
+
+    let intent = env
+        .call_method(&activity, "getIntent", "()Landroid/content/Intent;", &[])
+        .ok()?
+        .l()
+        .ok()?;
+    let action: JString = env
+        .call_method(&intent, "getAction", "()Ljava/lang/String;", &[])
+        .ok()?
+        .l()
+        .ok()?
+        .into();
+    if action.is_null() {
+        return None;
+    }
+    let action: String = env.get_string(&action).ok()?.into();
+    if action != "android.intent.action.SEND" {
+        return None;
+    }
+
+    let extra_name = env.new_string("android.intent.extra.TEXT").ok()?;
+    let text: JString = env
+        .call_method(
+            &intent,
+            "getStringExtra",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[(&extra_name).into()],
+        )
+        .ok()?
+        .l()
+        .ok()?
+        .into();
+    if text.is_null() {
+        return None;
+    }
+    env.get_string(&text).ok().map(Into::into)
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn received_text() -> Option<String> {
+    None
+}
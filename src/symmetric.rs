@@ -0,0 +1,161 @@
+//! Passphrase-only encryption using a symmetric-key encrypted session key
+//! (SKESK) packet, producing the same kind of message as `gpg -c`, or with
+//! Argon2 S2K the v6 SKESK and SEIPD v2 packets of RFC 9580.
+
+use std::{fmt, str::FromStr};
+
+pub use pgp::native::crypto::{hash::HashAlgorithm, sym::SymmetricKeyAlgorithm};
+use pgp::native::{
+    errors::Error as PgpError, ser::Serialize, types::StringToKey, Deserializable, Esk, Message,
+};
+
+use zeroize::Zeroizing;
+
+use crate::compression::{self, Compression};
+use crate::literal::{self, LiteralInfo, LiteralOptions};
+use crate::{armor, packets, rfc9580, Error, Field, Result};
+
+/// How the message key is derived from the passphrase.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum S2kKind {
+    /// Iterated and salted S2K, readable by every OpenPGP implementation.
+    #[default]
+    Iterated,
+    /// The memory-hard Argon2 S2K of RFC 9580, which only comes with a v6
+    /// SKESK and SEIPD v2 and so is not readable by GnuPG.
+    Argon2,
+}
+
+impl S2kKind {
+    pub const ALL: [S2kKind; 2] = [S2kKind::Iterated, S2kKind::Argon2];
+
+    /// Short name, as accepted by [`S2kKind::from_str`].
+    pub fn name(self) -> &'static str {
+        match self {
+            S2kKind::Iterated => "iterated",
+            S2kKind::Argon2 => "argon2",
+        }
+    }
+}
+
+impl fmt::Display for S2kKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for S2kKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        S2kKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown S2K \"{}\", use iterated or argon2", s))
+    }
+}
+
+/// Cipher and S2K parameters used to derive the message key from the
+/// passphrase. The hash and iteration count only apply to iterated and
+/// salted S2K.
+#[derive(Clone, Copy, PartialEq)]
+pub struct S2kOptions {
+    pub cipher: SymmetricKeyAlgorithm,
+    pub kind: S2kKind,
+    pub hash: HashAlgorithm,
+    /// Coded iteration count as stored in the S2K specifier.
+    pub count: u8,
+}
+
+impl Default for S2kOptions {
+    fn default() -> Self {
+        S2kOptions {
+            cipher: SymmetricKeyAlgorithm::AES256,
+            kind: S2kKind::Iterated,
+            hash: HashAlgorithm::SHA2_256,
+            count: 224,
+        }
+    }
+}
+
+/// Number of bytes hashed for a coded S2K iteration count.
+pub fn decoded_count(count: u8) -> u32 {
+    (16 + (count as u32 & 15)) << ((count as u32 >> 4) + 6)
+}
+
+pub fn encrypt(
+    passphrase: &str,
+    data: &[u8],
+    options: S2kOptions,
+    literal: &LiteralOptions,
+    compression: Compression,
+) -> Result<String> {
+    if passphrase.is_empty() {
+        return Err(Error::MissingInput(Field::Passphrase));
+    }
+    let msg = literal::message(data, literal)
+        .and_then(|msg| compression::compress(msg, compression))
+        .map_err(Error::Encrypt)?;
+    if options.kind == S2kKind::Argon2 {
+        let msg = msg.to_bytes().map(Zeroizing::new).map_err(|e| Error::Encrypt(e.to_string()))?;
+        let encrypted = rfc9580::encrypt_with_password(passphrase, &msg, options.cipher)?;
+        return Ok(armor::encode("PGP MESSAGE", &[], &encrypted));
+    }
+    let mut rng = rand::thread_rng();
+    let s2k = StringToKey::new_iterated(&mut rng, options.hash, options.count);
+    let passphrase = passphrase.to_string();
+
+    msg.encrypt_with_password(&mut rng, s2k, options.cipher, || passphrase)
+        .and_then(|msg| msg.to_armored_string(None))
+        .map_err(|e| Error::Encrypt(e.to_string()))
+}
+
+/// Decrypts a passphrase-only message. Only a session key that cannot be
+/// recovered from the SKESK packet means the passphrase does not match;
+/// integrity (MDC or AEAD) failures and broken packets are reported as
+/// they are.
+pub fn decrypt(passphrase: &str, armored: &str) -> Result<(LiteralInfo, Zeroizing<Vec<u8>>)> {
+    if passphrase.is_empty() {
+        return Err(Error::MissingInput(Field::Passphrase));
+    }
+    // A v6 SKESK, as written with Argon2 S2K, goes with SEIPD v2, which
+    // only the newer OpenPGP library reads.
+    let v6 = armor::decode_all(armored)
+        .ok()
+        .and_then(|blocks| blocks.into_iter().next())
+        .map(|block| block.data)
+        .filter(|data| packets::session_keys(data).is_ok_and(|keys| keys.rfc9580));
+    if let Some(data) = v6 {
+        let inner = rfc9580::decrypt_with_password(passphrase, &data)?;
+        let msg = Message::from_bytes(&inner[..]).map_err(|e| Error::Decrypt(e.to_string()))?;
+        let msg = compression::decompress(msg).map_err(Error::Decrypt)?;
+        return literal::read(&msg).map_err(Error::Decrypt);
+    }
+    let (msg, _) = Message::from_string(armored)
+        .map_err(|e| Error::MalformedArmor(Field::EncryptedMessage, e.to_string()))?;
+    let has_skesk = match &msg {
+        Message::Encrypted { esk, .. } => {
+            esk.iter().any(|esk| matches!(esk, Esk::SymKeyEncryptedSessionKey(_)))
+        }
+        _ => false,
+    };
+    if !has_skesk {
+        return Err(Error::Decrypt("The message is not encrypted with a passphrase".to_string()));
+    }
+    let passphrase = passphrase.to_string();
+    let mut decrypter = msg
+        .decrypt_with_password(|| passphrase)
+        .map_err(|_| Error::WrongPassphrase)?;
+    let msg = decrypter
+        .next()
+        .ok_or_else(|| Error::Decrypt("Message contains no encrypted data".to_string()))?
+        .map_err(|e| match e {
+            // The session key does not fit its cipher, so the SKESK packet
+            // was decrypted with the wrong passphrase.
+            PgpError::CfbInvalidKeyIvLength => Error::WrongPassphrase,
+            e => Error::Decrypt(e.to_string()),
+        })?;
+
+    let msg = compression::decompress(msg).map_err(Error::Decrypt)?;
+    literal::read(&msg).map_err(Error::Decrypt)
+}
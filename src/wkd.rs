@@ -0,0 +1,62 @@
+//! Web Key Directory (WKD) lookup URLs, using the direct method from
+//! draft-koch-openpgp-webkey-service.
+
+use sha1::{Digest, Sha1};
+
+const ZBASE32_ALPHABET: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+
+/// Returns the WKD URL for `email`, or `None` when it is not an address.
+pub fn url(email: &str) -> Option<String> {
+    let (local, domain) = email.trim().rsplit_once('@')?;
+    if local.is_empty() || domain.is_empty() {
+        return None;
+    }
+    let hash = Sha1::digest(local.to_lowercase().as_bytes());
+    Some(format!(
+        "https://{}/.well-known/openpgpkey/hu/{}?l={}",
+        domain.to_lowercase(),
+        zbase32(&hash),
+        percent_encode(local)
+    ))
+}
+
+/// Extracts the address from a user ID such as `Alice <alice@example.org>`.
+pub fn email_from_user_id(user_id: &str) -> Option<&str> {
+    let email = match (user_id.rfind('<'), user_id.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &user_id[start + 1..end],
+        _ => user_id.trim(),
+    };
+    email.contains('@').then_some(email)
+}
+
+/// Percent-encodes everything but unreserved characters (RFC 3986,
+/// section 2.3), as the draft asks for the `l` parameter.
+fn percent_encode(text: &str) -> String {
+    let mut out = String::new();
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+fn zbase32(data: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ZBASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ZBASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
@@ -0,0 +1,957 @@
+use another_pgp_tool::audit::{self, FixIt, Severity};
+use another_pgp_tool::compression::Compression;
+use another_pgp_tool::detect::{self, Content};
+use another_pgp_tool::literal::{DataMode, LiteralInfo, LiteralOptions};
+use another_pgp_tool::{
+    armor, crypto, fingerprint, merge, packets, preferences, shamir, symmetric, wkd, Error,
+    Field,
+};
+
+const WIKIPEDIA_FINGERPRINT: &str = "E58294F2E9A227486E8B061B31CC528FD7FA3F19";
+
+/// RSA-1024 key made with GnuPG on 2020-09-13 that may only certify and
+/// sign, self-signed with SHA-1.
+const WEAK_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mI0EX14QAAEEAMoxes+YB7zs8y5jYT+N+Wg0hTiiHD+bddewWiOe6xiIdTJcoZCE
+GPYmdB+wrEZThQ472unT0fORJ7QGBAdUqgqBZp8l+GzoGu4JVlK864ERHBVXvhRD
+lF6jgniwHku7b5zsT4edit9+L4qfG9pN0HD2GB+pw2M6Ooyq3Y84dP7tABEBAAG0
+F1dlYWsgPHdlYWtAZXhhbXBsZS5vcmc+iM4EEwECADgWIQSRt41kcZKAZg7UQrYL
+ZdIpMeWUUAUCX14QAAIbAwULCQgHAgYVCgkICwIEFgIDAQIeAQIXgAAKCRALZdIp
+MeWUUI6MBACqsh18TTpyaKYxHootztfzNLUu5YsUFCcJJUYh9zpaMAfbMdgM34rZ
+XQ1zaO4YMHdR7QVl3r9msSCNX15h3ekxWLTZ2VFz4+NfgbnUcEP+mwsF27QLsRaG
+JEDUaz8w+TOHh/WjfmgU8XXQluNYRkSMk/kysJVmfxJ2VZ+ZKSQX4w==
+=QQB3
+-----END PGP PUBLIC KEY BLOCK-----";
+
+/// RSA-1024 key made with GnuPG on 2020-09-13 whose primary key may
+/// encrypt, without subkeys.
+const PRIMARY_ONLY_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mI0EX14QAAEEAMQ9eLINKf1LrHqobtmFX1OzbaJE6b5WBXWMfORwApfgK+llpO0Z
+/lQIhCJaO1Zdlk9Oz6o5Mrg+rN1JKDS5vSFz14INyBIHpqSbCXGcLTipGENliCj3
+2SDyhVJnhm7mKfkFU5doYW5Btlk9B0j89PXzGF7hGwVzBppuK9CMwLi1ABEBAAG0
+F1NvbG8gPHNvbG9AZXhhbXBsZS5vcmc+iM4EEwEKADgWIQSIyDGfKHwmXlNmlbZ6
+oUzKffBG3wUCX14QAAIbDwULCQgHAgYVCgkICwIEFgIDAQIeAQIXgAAKCRB6oUzK
+ffBG38gHA/98TI6TYAYk2Ym7ScgxdAXxTGBAZldZbOAPAKnuE4OdbrTXgGVQsYJW
+v0s3+ghcWOYE9AS/1pFWQxWhhI5CUkusOflxsutLLniiqPSiDwddHtexxSdpClNG
+lpoM9cTMW6/uEoIXy+6XtuW05FosIPmIcbBKDwPcziGrJ77vtXhzOw==
+=FR/b
+-----END PGP PUBLIC KEY BLOCK-----";
+
+/// Ed25519 key with a Cv25519 encryption subkey made with GnuPG on
+/// 2020-09-13 12:26:40 UTC, both valid for one day.
+const EXPIRED_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEX14QABYJKwYBBAHaRw8BAQdA9CNignfIm3AuAa5/btO9xQl89EI+Ax2M2KHa
+SDVxZS+0F0dvbmUgPGdvbmVAZXhhbXBsZS5vcmc+iJYEExYIAD4WIQRL2bVZNqDN
+9F6sUraJTvy9RjUwvgUCX14QAAIbAwUJAAFRgAULCQgHAgYVCgkICwIEFgIDAQIe
+AQIXgAAKCRCJTvy9RjUwvmSXAP49Ht3/8Gqus3lFWQXdj8xOpMPsR0hL7MhLvi7Q
+PlqXewD+MOZJ0OJlC3iSN05eYP/nBr9tNPgNKu/pgaiWhYqaZgy4OARfXhAAEgor
+BgEEAZdVAQUBAQdAFwAJWHrY7hreX/o83WhM7yCFySiEOmUNlRnCLfSU1QsDAQgH
+iH4EGBYIACYWIQRL2bVZNqDN9F6sUraJTvy9RjUwvgUCX14QAAIbDAUJAAFRgAAK
+CRCJTvy9RjUwvsdnAQCsRVMAJ1X1/kDnG48etc4Fieel5+IqqvbQlEyzeY0hPwD+
+JfmVI3ZCyB+hrWiwRTRHYHpWAnqrVMljNTEfU0HlJAs=
+=0HZn
+-----END PGP PUBLIC KEY BLOCK-----";
+
+/// [`EXPIRED_KEY`] with a second user ID.
+const EXPIRED_KEY_TWO_USER_IDS: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEX14QABYJKwYBBAHaRw8BAQdA9CNignfIm3AuAa5/btO9xQl89EI+Ax2M2KHa
+SDVxZS+0F0dvbmUgPGdvbmVAZXhhbXBsZS5vcmc+iJYEExYIAD4WIQRL2bVZNqDN
+9F6sUraJTvy9RjUwvgUCX14QAAIbAwUJAAFRgAULCQgHAgYVCgkICwIEFgIDAQIe
+AQIXgAAKCRCJTvy9RjUwvmSXAP49Ht3/8Gqus3lFWQXdj8xOpMPsR0hL7MhLvi7Q
+PlqXewD+MOZJ0OJlC3iSN05eYP/nBr9tNPgNKu/pgaiWhYqaZgy0GEdvbmUgPGdv
+bmVAd29yay5leGFtcGxlPoiWBBMWCAA+FiEES9m1WTagzfRerFK2iU78vUY1ML4F
+Al9eEAACGwMFCQABUYAFCwkIBwIGFQoJCAsCBBYCAwECHgECF4AACgkQiU78vUY1
+ML5i8wD8DDlAkzI21hRqxxJaKzUQpqoa5H0+ueZ2HfECOtA1rPgBALur6HER0Vcr
+xNzREodErMkZ1mauKxQTQEt1gHQUNzULuDgEX14QABIKKwYBBAGXVQEFAQEHQBcA
+CVh62O4a3l/6PN1oTO8ghckohDplDZUZwi30lNULAwEIB4h+BBgWCAAmFiEES9m1
+WTagzfRerFK2iU78vUY1ML4FAl9eEAACGwwFCQABUYAACgkQiU78vUY1ML7HZwEA
+rEVTACdV9f5A5xuPHrXOBYnnpefiKqr20JRMs3mNIT8A/iX5lSN2Qsgfoa1osEU0
+R2B6VgJ6q1TJYzUxH1NB5SQL
+=Rm/O
+-----END PGP PUBLIC KEY BLOCK-----";
+
+/// The revocation certificate GnuPG made for [`EXPIRED_KEY`].
+const EXPIRED_KEY_REVOCATION: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+Comment: This is a revocation certificate
+
+iHgEIBYIACAWIQRL2bVZNqDN9F6sUraJTvy9RjUwvgUCX14QAAIdAAAKCRCJTvy9
+RjUwvvGjAP0YZx/yIN3SUp6weQRMPcNgQYwaNVTyrledXZbZjWvmfgEA3eBGeV6w
+8rfXnMoxilPMlMyXG4BsW+NklxNSfsIpJQA=
+=k+BY
+-----END PGP PUBLIC KEY BLOCK-----";
+
+/// Ed25519 key with a Curve25519 encryption subkey whose primary user ID
+/// announces SEIPD v2 with AES-256 and OCB, dated 2020-09-13.
+const SEIPD_V2_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEX14QABYJKwYBBAHaRw8BAQdAebVWLo/mVPlAeLES6KmLp5AfhTrmlb7X4OOR
+C60ElmS0F0FFQUQgPGFlYWRAZXhhbXBsZS5vcmc+iI0EExYIADUFAl9eEAAWIQQ/
+I09Fl5guIntJtlpXEYmGGeHoNgIbAwMLCQcDFQoIAxYCAQIeCQUnCQIHAgAKCRBX
+EYmGGeHoNvXwAP9mk8j9OsadRmc5xUSQRAJDdPCV8kHyKxxNhe/jAPOO2wD/V+3l
+j2ManS8UpEhZpYyHbEy1VGQLK+6KmpOsE+/yzgK4OARfXhAAEgorBgEEAZdVAQUB
+AQdAWGmv9FBUlzLLqu1eXfmzCm2jHLDldCutWtShp2jxpnsDAQgHiHgEGBYIACAF
+Al9eEAAWIQQ/I09Fl5guIntJtlpXEYmGGeHoNgIbDAAKCRBXEYmGGeHoNlukAP9H
+U6JT/V0RAxc2VFnY9YzqaLVYxV3B8UVcoizxrOUB/AD+N11TwzvP/iVPK0vy9tt8
+jI99RP2Rerx1OxHTQPgsrgw=
+=2r3f
+-----END PGP PUBLIC KEY BLOCK-----";
+
+/// Ed25519 key whose cipher preference (AES-128) and SEIPD v2 feature are
+/// only stated in a direct-key signature, dated 2020-09-13.
+const DIRECT_KEY_PREFERENCES_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+xjMEX14QABYJKwYBBAHaRw8BAQdAebVWLo/mVPlAeLES6KmLp5AfhTrmlb7X4OOR
+C60ElmTCfgQfFggAJgUCX14QABYhBD8jT0WXmC4ie0m2WlcRiYYZ4eg2AgsHAhUI
+Ah4JAAoJEFcRiYYZ4eg2dtkBAJuHWnmD0a3PSlYL1Ph6lEQWbADmuM+uYJe/aOBb
+mdHSAP9ALbpnnvGCuW1kVe7cvZYv7SF8Kvz0xwIJ3qUlTkZ1CM0bRGlyZWN0IDxk
+aXJlY3RAZXhhbXBsZS5vcmc+wngEExYIACAFAl9eEAAWIQQ/I09Fl5guIntJtlpX
+EYmGGeHoNgIbAwAKCRBXEYmGGeHoNoT1AQCtQFxMC6a9D2ocMNOIoa9jhuFdul/6
+xhtxUkQzuvWyMAD+OViaRuCkAeEWibInssn0hfQUX2hKLGgfawEbUsIT1gc=
+=y8bP
+-----END PGP PUBLIC KEY BLOCK-----
+";
+
+/// Encrypts with the default file name, date, format and compression.
+async fn encrypt(public_key: &str, text: &str) -> String {
+    crypto::encrypt_text(public_key, text, &LiteralOptions::default(), None, false)
+        .await
+        .unwrap()
+}
+
+/// The binary form of the first armored block in `armored`.
+fn dearmor(armored: &str) -> Vec<u8> {
+    armor::decode_all(armored).unwrap().remove(0).data.to_vec()
+}
+
+/// A new format packet with a body shorter than 192 bytes.
+fn packet(tag: u8, body: &[u8]) -> Vec<u8> {
+    [&[0xc0 | tag, body.len() as u8][..], body].concat()
+}
+
+#[tokio::test]
+async fn encrypt_decrypt_roundtrip() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let encrypted = encrypt(&keys.public_key, "hello world").await;
+    assert!(encrypted.contains("-----BEGIN PGP MESSAGE-----"));
+
+    let decrypted = crypto::decrypt_text(&keys.secret_key, "", &encrypted)
+        .await
+        .unwrap();
+    assert_eq!(decrypted.text.as_str(), "hello world");
+}
+
+#[tokio::test]
+async fn decrypt_with_wrong_key_fails() {
+    let alice = crypto::generate_keypair().await.unwrap();
+    let bob = crypto::generate_keypair().await.unwrap();
+    let encrypted = encrypt(&alice.public_key, "secret").await;
+
+    let result = crypto::decrypt_text(&bob.secret_key, "", &encrypted).await;
+    assert_eq!(result.err(), Some(Error::NoMatchingKey));
+}
+
+#[tokio::test]
+async fn sign_verify_roundtrip() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let signed = crypto::sign_cleartext(&keys.secret_key, "", "signed text")
+        .await
+        .unwrap();
+    assert!(signed.starts_with("-----BEGIN PGP SIGNED MESSAGE-----"));
+
+    let message = crypto::verify_cleartext(&keys.public_key, &signed)
+        .await
+        .unwrap();
+    assert_eq!(message, "signed text");
+}
+
+#[tokio::test]
+async fn inline_sign_verify_roundtrip() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let data = [0u8, 1, 2, b'\r', b'\n', 0xff];
+    let literal = LiteralOptions {
+        file_name: "data.bin".to_string(),
+        date: Some(0x5f5e1000),
+        mode: DataMode::Binary,
+    };
+    for compression in Compression::ALL {
+        let signed = crypto::sign_inline(&keys.secret_key, "", &data, &literal, compression)
+            .await
+            .unwrap();
+        assert!(signed.starts_with("-----BEGIN PGP MESSAGE-----"));
+        let first = crypto::list_packets(signed.as_bytes()).unwrap()[0].tag;
+        assert_eq!(first, if compression == Compression::None { 4 } else { 8 });
+
+        let (info, verified) = crypto::verify_inline(&keys.public_key, &signed).await.unwrap();
+        assert_eq!(&verified[..], &data[..], "{}", compression);
+        assert_eq!(info.file_name, "data.bin");
+        assert_eq!(info.date, 0x5f5e1000);
+    }
+
+    let signed = crypto::sign_inline(&keys.secret_key, "", &data, &literal, Compression::None)
+        .await
+        .unwrap();
+    let mut tampered = dearmor(&signed);
+    let at = tampered.windows(3).position(|w| w == [0, 1, 2]).unwrap();
+    tampered[at] = 7;
+    let tampered = armor::encode("PGP MESSAGE", &[], &tampered);
+    let result = crypto::verify_inline(&keys.public_key, &tampered).await;
+    assert!(matches!(result, Err(Error::BadSignature(_))), "{:?}", result.err());
+
+    let other = crypto::generate_keypair().await.unwrap();
+    let result = crypto::verify_inline(&other.public_key, &signed).await;
+    assert!(matches!(result, Err(Error::BadSignature(_))));
+    let encrypted = encrypt(&keys.public_key, "not signed").await;
+    let result = crypto::verify_inline(&keys.public_key, &encrypted).await;
+    assert!(matches!(result, Err(Error::InvalidSignedMessage(_))));
+}
+
+#[tokio::test]
+async fn verify_detects_tampering() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let signed = crypto::sign_cleartext(&keys.secret_key, "", "original")
+        .await
+        .unwrap();
+    let tampered = signed.replace("original", "modified");
+
+    let result = crypto::verify_cleartext(&keys.public_key, &tampered).await;
+    assert!(matches!(result, Err(Error::BadSignature(_))));
+}
+
+#[tokio::test]
+async fn public_key_in_private_key_field_is_reported() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let result = crypto::sign_cleartext(&keys.public_key, "", "text").await;
+    let err = result.err().unwrap();
+    assert!(matches!(err, Error::WrongKeyType { .. }));
+    assert_eq!(err.field(), Some(Field::SecretKey));
+}
+
+#[tokio::test]
+async fn decrypt_rejects_non_message_input() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let result = crypto::decrypt_text(&keys.secret_key, "", "hello").await;
+    assert!(matches!(
+        result.err(),
+        Some(Error::MalformedArmor(Field::EncryptedMessage, _))
+    ));
+}
+
+#[tokio::test]
+async fn verify_requires_input() {
+    let result = crypto::verify_cleartext("", "").await;
+    assert!(matches!(result, Err(Error::MissingInput(_))));
+}
+
+#[test]
+fn split_cleartext_extracts_message() {
+    let signed = "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\nline one\nline two\n\
+                  -----BEGIN PGP SIGNATURE-----\nabc\n-----END PGP SIGNATURE-----";
+    let (message, signature) = crypto::split_cleartext(signed).unwrap();
+    assert_eq!(message, "line one\nline two");
+    assert!(signature.starts_with("-----BEGIN PGP SIGNATURE-----"));
+    assert!(signature.ends_with("-----END PGP SIGNATURE-----"));
+}
+
+#[test]
+fn split_cleartext_rejects_missing_signature() {
+    let result = crypto::split_cleartext("just some text");
+    assert!(matches!(result, Err(Error::InvalidSignedMessage(_))));
+}
+
+#[test]
+fn passphrase_roundtrip() {
+    let options = symmetric::S2kOptions::default();
+    let encrypted = crypto::encrypt_text_with_passphrase(
+        "correct horse",
+        "hi",
+        options,
+        &LiteralOptions::default(),
+        Compression::Zlib,
+    )
+    .unwrap();
+    assert_eq!(
+        crypto::decrypt_text_with_passphrase("correct horse", &encrypted)
+            .unwrap()
+            .text
+            .as_str(),
+        "hi"
+    );
+    assert_eq!(
+        crypto::decrypt_text_with_passphrase("wrong", &encrypted).err(),
+        Some(Error::WrongPassphrase)
+    );
+
+    // A damaged message is not blamed on the passphrase.
+    let mut data = dearmor(&encrypted);
+    *data.last_mut().unwrap() ^= 0xff;
+    let tampered = armor::encode("PGP MESSAGE", &[], &data);
+    assert!(matches!(
+        crypto::decrypt_text_with_passphrase("correct horse", &tampered),
+        Err(Error::Decrypt(_))
+    ));
+}
+
+#[test]
+fn argon2_passphrase_roundtrip() {
+    let options = symmetric::S2kOptions {
+        kind: symmetric::S2kKind::Argon2,
+        ..Default::default()
+    };
+    let encrypted = crypto::encrypt_text_with_passphrase(
+        "correct horse",
+        "hi",
+        options,
+        &LiteralOptions::default(),
+        Compression::Zlib,
+    )
+    .unwrap();
+    let packets = crypto::list_packets(encrypted.as_bytes()).unwrap();
+    assert_eq!((packets[0].tag, packets[0].version), (3, Some(6)));
+    assert!(packets[0].details.contains(&"S2K Argon2".to_string()), "{:?}", packets[0].details);
+    assert_eq!((packets[1].tag, packets[1].version), (18, Some(2)));
+    assert_eq!(
+        crypto::decrypt_text_with_passphrase("correct horse", &encrypted)
+            .unwrap()
+            .text
+            .as_str(),
+        "hi"
+    );
+    assert_eq!(
+        crypto::decrypt_text_with_passphrase("wrong", &encrypted).err(),
+        Some(Error::WrongPassphrase)
+    );
+
+    let mut data = dearmor(&encrypted);
+    *data.last_mut().unwrap() ^= 0xff;
+    let tampered = armor::encode("PGP MESSAGE", &[], &data);
+    assert!(matches!(
+        crypto::decrypt_text_with_passphrase("correct horse", &tampered),
+        Err(Error::Decrypt(_))
+    ));
+}
+
+#[tokio::test]
+async fn inspect_reports_fingerprint() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let info = crypto::inspect_public_key(&keys.public_key).await.unwrap();
+    assert_eq!(info.fingerprint.len(), 20);
+}
+
+#[tokio::test]
+async fn paper_backup_roundtrip() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let backup = crypto::create_paper_backup(&keys.secret_key).await.unwrap();
+
+    let restored = crypto::restore_paper_backup(&keys.public_key, &backup.text)
+        .await
+        .unwrap();
+    let encrypted = encrypt(&keys.public_key, "paper").await;
+    assert_eq!(
+        crypto::decrypt_text(&restored, "", &encrypted)
+            .await
+            .unwrap()
+            .text
+            .as_str(),
+        "paper"
+    );
+
+    let from_qr = crypto::restore_paper_backup(&keys.public_key, &backup.qr_chunks.join("\n"))
+        .await
+        .unwrap();
+    assert_eq!(from_qr, restored);
+
+    let first = &backup.qr_chunks[0];
+    let twice = format!("{}\n{}", first, backup.qr_chunks.join("\n"));
+    assert!(crypto::restore_paper_backup(&keys.public_key, &twice).await.is_err());
+    let total = format!("/{} ", backup.qr_chunks.len());
+    let out_of_range = first.replacen(&total, &format!("/{} ", backup.qr_chunks.len() + 1), 1);
+    let zero = first.replacen("APT-PAPER 1/", "APT-PAPER 0/", 1);
+    for bad in [out_of_range, zero] {
+        assert!(crypto::restore_paper_backup(&keys.public_key, &bad).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn paper_backup_detects_typos() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let backup = crypto::create_paper_backup(&keys.secret_key).await.unwrap();
+    let line = backup.text.lines().find(|l| l.starts_with("  2:")).unwrap();
+    let byte = if &line[5..7] == "00" { "01" } else { "00" };
+    let mangled = backup
+        .text
+        .replace(line, &format!("{}{}{}", &line[..5], byte, &line[7..]));
+
+    let result = crypto::restore_paper_backup(&keys.public_key, &mangled).await;
+    assert!(matches!(result, Err(Error::PaperBackup(_))));
+}
+
+#[tokio::test]
+async fn secret_key_shares_roundtrip() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let shares = crypto::split_secret(&keys.secret_key, shamir::SecretKind::SecretKey, 2, 3)
+        .await
+        .unwrap();
+    assert_eq!(shares.len(), 3);
+
+    let (kind, secret) = crypto::combine_shares(&format!("{}{}", shares[0], shares[2]))
+        .await
+        .unwrap();
+    assert!(kind == shamir::SecretKind::SecretKey);
+    assert!(secret.contains("PGP PRIVATE KEY BLOCK"));
+}
+
+#[tokio::test]
+async fn too_few_shares_fail() {
+    let shares = crypto::split_secret("hunter2", shamir::SecretKind::Passphrase, 3, 5)
+        .await
+        .unwrap();
+    let result = crypto::combine_shares(&format!("{}{}", shares[0], shares[1])).await;
+    assert!(matches!(result, Err(Error::SecretSharing(_))));
+
+    let (_, secret) = crypto::combine_shares(&shares[1..4].concat())
+        .await
+        .unwrap();
+    assert_eq!(secret.as_str(), "hunter2");
+
+    // Shares of another split relabeled as this one combine to garbage,
+    // which the checksum catches.
+    let other = crypto::split_secret("hunter3", shamir::SecretKind::Passphrase, 3, 5)
+        .await
+        .unwrap();
+    let set = |share: &str| share.lines().find(|l| l.starts_with("Set: ")).unwrap().to_string();
+    let relabeled = other[2].replace(&set(&other[2]), &set(&shares[0]));
+    let mixed = format!("{}{}{}", shares[0], shares[1], relabeled);
+    let result = crypto::combine_shares(&mixed).await;
+    assert!(matches!(result, Err(Error::SecretSharing(e)) if e.contains("do not combine")));
+    let result = crypto::combine_shares(&format!("{}{}", shares[0], other[1])).await;
+    assert!(matches!(result, Err(Error::SecretSharing(e)) if e.contains("different splits")));
+}
+
+#[test]
+fn fingerprint_words_match_reference() {
+    let fpr = fingerprint::parse(WIKIPEDIA_FINGERPRINT).unwrap();
+    assert_eq!(
+        fingerprint::to_words(&fpr).join(" "),
+        "topmost Istanbul Pluto vagabond treadmill Pacific brackish dictator goldfish Medusa \
+         afflict bravado chatter revolver Dupont midsummer stopwatch whimsical cowbell bottomless"
+    );
+}
+
+#[test]
+fn fingerprint_parse_accepts_common_formats() {
+    let fpr = fingerprint::parse(WIKIPEDIA_FINGERPRINT).unwrap();
+    let grouped = fingerprint::to_grouped_hex(&fpr);
+    assert_eq!(grouped, "E582 94F2 E9A2 2748 6E8B  061B 31CC 528F D7FA 3F19");
+
+    assert_eq!(fingerprint::parse(&grouped), Some(fpr.clone()));
+    assert_eq!(fingerprint::parse(&grouped.to_lowercase()), Some(fpr.clone()));
+    assert_eq!(
+        fingerprint::parse(&fingerprint::openpgp4fpr_uri(&fpr)),
+        Some(fpr.clone())
+    );
+    assert_eq!(
+        fingerprint::parse(&fingerprint::to_words(&fpr).join(" ")),
+        Some(fpr)
+    );
+    assert_eq!(fingerprint::parse("not a fingerprint"), None);
+}
+
+#[test]
+fn wkd_url_matches_reference() {
+    assert_eq!(
+        wkd::url("Joe.Doe@Example.ORG").as_deref(),
+        Some("https://example.org/.well-known/openpgpkey/hu/iy9q119eutrkn8s1mk4r39qejnbu3n5q?l=Joe.Doe")
+    );
+    assert_eq!(
+        wkd::email_from_user_id("Joe Doe <joe.doe@example.org>"),
+        Some("joe.doe@example.org")
+    );
+    assert_eq!(wkd::email_from_user_id("Joe Doe"), None);
+    let url = wkd::url("a+b&c%d e@example.org").unwrap();
+    assert!(url.ends_with("?l=a%2Bb%26c%25d%20e"), "{}", url);
+}
+
+#[test]
+fn armor_roundtrip_and_checksum() {
+    let headers = vec![("Comment".to_string(), "test".to_string())];
+    let armored = armor::encode("PGP MESSAGE", &headers, b"some binary data");
+    let blocks = armor::decode_all(&armored).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].label, "PGP MESSAGE");
+    assert_eq!(blocks[0].headers, headers);
+    assert_eq!(*blocks[0].data, b"some binary data");
+
+    let corrupted = armored.replacen("c29t", "c29u", 1);
+    assert!(armor::decode_all(&corrupted).is_err());
+}
+
+#[test]
+fn crc24_matches_reference() {
+    assert_eq!(armor::crc24(b""), 0xB704CE);
+    assert_eq!(armor::crc24(b"123456789"), 0x21CF02);
+}
+
+#[test]
+fn dearmor_reports_checksum_and_repairs_email_damage() {
+    let headers = vec![("Comment".to_string(), "a long comment here".to_string())];
+    let armored = armor::encode("PGP MESSAGE", &headers, &[0x55; 100]);
+    let block = crypto::dearmor(&armored).unwrap();
+    assert_eq!(block.checksum, armor::Checksum::Valid);
+    assert!(!block.repaired);
+
+    let mangled: String = armored
+        .replace("a long comment", "a long\ncomment")
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| format!(">  {}\n", line))
+        .collect();
+    let block = crypto::dearmor(&mangled).unwrap();
+    assert!(block.repaired);
+    assert_eq!(block.headers, headers);
+    assert_eq!(*block.data, [0x55; 100]);
+
+    let crc_line = armored.lines().find(|line| line.starts_with('=')).unwrap();
+    let block = crypto::dearmor(&armored.replace(crc_line, "=AAAA")).unwrap();
+    assert!(matches!(block.checksum, armor::Checksum::Mismatch { .. }));
+
+    let rearmored = crypto::enarmor(&block.data, None, "Comment: edited").unwrap();
+    assert!(rearmored.starts_with("-----BEGIN PGP ARMORED FILE-----\nComment: edited\n\n"));
+    assert!(crypto::enarmor(&block.data, None, "no colon").is_err());
+}
+
+#[tokio::test]
+async fn detects_armored_and_binary_content() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let (content, text) = detect::detect(keys.public_key.as_bytes()).unwrap();
+    assert_eq!(content, Content::PublicKey);
+    assert_eq!(text, keys.public_key.trim());
+
+    let encrypted = encrypt(&keys.public_key, "hi").await;
+    let binary = armor::decode_all(&encrypted).unwrap().remove(0).data;
+    let (content, text) = detect::detect(&binary).unwrap();
+    assert_eq!(content, Content::Message);
+    assert_eq!(armor::decode_all(&text).unwrap()[0].data, binary);
+
+    assert_eq!(detect::detect(b"just some text"), None);
+}
+
+#[tokio::test]
+async fn lists_packets_without_secret_material() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let packets = crypto::list_packets(keys.secret_key.as_bytes()).unwrap();
+    assert_eq!(packets[0].tag, 5);
+    assert!(packets.iter().any(|p| p.tag == 13));
+    let listing = packets::to_text(&packets);
+    assert!(listing.contains("(not shown)"));
+
+    let encrypted = encrypt(&keys.public_key, "hi").await;
+    let packets = crypto::list_packets(encrypted.as_bytes()).unwrap();
+    assert_eq!(packets[0].tag, 1);
+    assert!(packets[0].details.iter().any(|d| d.starts_with("key ID ")));
+    assert!(packets.iter().any(|p| p.tag == 18));
+}
+
+#[test]
+fn list_packets_rejects_broken_framing() {
+    assert!(matches!(crypto::list_packets(&[0x01, 0x02]), Err(Error::Packets(_))));
+    assert_eq!(
+        crypto::list_packets(b"  "),
+        Err(Error::MissingInput(Field::PacketData))
+    );
+}
+
+#[tokio::test]
+async fn rfc9580_keys_and_signatures_are_listed() {
+    let created = [0x63, 0x87, 0x7f, 0xe3];
+    let mut key = vec![0xc6, 42, 6];
+    key.extend_from_slice(&created);
+    key.extend_from_slice(&[27, 0, 0, 0, 32]);
+    key.extend_from_slice(&[0x11; 32]);
+    let mut sig = vec![0xc2, 117, 6, 0x1f, 27, 10, 0, 0, 0, 6, 5, 2];
+    sig.extend_from_slice(&created);
+    sig.extend_from_slice(&[0, 0, 0, 0, 0xab, 0xcd, 32]);
+    sig.extend_from_slice(&[0x22; 32]);
+    sig.extend_from_slice(&[0x33; 64]);
+    let data = [key, sig].concat();
+
+    let packets = crypto::list_packets(&data).unwrap();
+    assert_eq!(packets[0].version, Some(6));
+    let details = &packets[0].details;
+    assert_eq!(details[1], format!("created {}", packets::format_time(0x63877fe3)));
+    assert_eq!(details[2], "algorithm Ed25519");
+    let fpr = details[3].strip_prefix("fingerprint ").unwrap();
+    assert_eq!(fpr.len(), 64);
+    assert_eq!(details[4], format!("key ID {}", &fpr[..16]));
+    assert_eq!(packets[1].version, Some(6));
+    assert!(packets[1].details.contains(&"salt: 32 bytes".to_string()));
+
+    let armored = crypto::enarmor(&data, Some("PGP PUBLIC KEY BLOCK"), "").unwrap();
+    assert!(matches!(
+        crypto::read_public_key(&armored).await,
+        Err(Error::UnsupportedAlgorithm(Field::PublicKey, _))
+    ));
+}
+
+#[tokio::test]
+async fn rfc9580_keys_encrypt_decrypt_sign_and_verify() {
+    let keys = crypto::generate_keypair_with_profile(crypto::KeyProfile::Rfc9580)
+        .await
+        .unwrap();
+    let packets = crypto::list_packets(keys.public_key.as_bytes()).unwrap();
+    assert_eq!(packets[0].tag, 6);
+    assert_eq!(packets[0].version, Some(6));
+    let info = crypto::inspect_public_key(&keys.public_key).await.unwrap();
+    assert_eq!(info.fingerprint.len(), 32);
+    let checked = crypto::check_recipient_keys(&keys.public_key).await.unwrap();
+    assert!(checked[0].usability.can_encrypt(), "{:?}", checked[0].usability);
+    let findings = crypto::audit_key(&keys.public_key).unwrap();
+    assert!(findings.iter().all(|f| f.severity != Severity::Critical), "{:?}", findings);
+
+    let encrypted = encrypt(&keys.public_key, "hello v6").await;
+    let packets = crypto::list_packets(encrypted.as_bytes()).unwrap();
+    assert_eq!((packets[0].tag, packets[0].version), (1, Some(6)));
+    assert_eq!((packets[1].tag, packets[1].version), (18, Some(2)));
+    let decrypted = crypto::decrypt_text(&keys.secret_key, "", &encrypted).await.unwrap();
+    assert_eq!(decrypted.text.as_str(), "hello v6");
+    let other = crypto::generate_keypair().await.unwrap();
+    let result = crypto::decrypt_text(&other.secret_key, "", &encrypted).await;
+    assert_eq!(result.err(), Some(Error::NoMatchingKey));
+
+    let signed = crypto::sign_cleartext(&keys.secret_key, "", "signed v6").await.unwrap();
+    let message = crypto::verify_cleartext(&keys.public_key, &signed).await.unwrap();
+    assert_eq!(message, "signed v6");
+    let tampered = signed.replace("signed v6", "forged v6");
+    let result = crypto::verify_cleartext(&keys.public_key, &tampered).await;
+    assert!(matches!(result, Err(Error::BadSignature(_))));
+
+    let literal = LiteralOptions::default();
+    let signed = crypto::sign_inline(&keys.secret_key, "", b"inline v6", &literal, Compression::Zip)
+        .await
+        .unwrap();
+    let (_, verified) = crypto::verify_inline(&keys.public_key, &signed).await.unwrap();
+    assert_eq!(&verified[..], b"inline v6");
+}
+
+#[tokio::test]
+async fn audit_reports_weak_keys() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let findings = crypto::audit_key(&keys.public_key).unwrap();
+    assert!(findings.iter().all(|f| f.severity != Severity::Critical), "{:?}", findings);
+
+    let findings = audit::audit(&dearmor(WEAK_KEY), 0x6000_0000).unwrap();
+    let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+    assert_eq!(findings.len(), 4, "{:?}", messages);
+    assert_eq!(findings[0].severity, Severity::Critical);
+    assert_eq!(findings[0].fix, Some(FixIt::GenerateKey));
+    assert!(messages[0].contains("RSA-1024"));
+    assert!(messages[1].starts_with("No usable encryption subkey"));
+    assert!(messages[2].contains("never expires"));
+    assert!(messages[3].contains("SHA-1"));
+}
+
+#[tokio::test]
+async fn unusable_recipient_keys_are_reported() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let checked = crypto::check_recipient_keys(&keys.public_key).await.unwrap();
+    assert_eq!(checked.len(), 1);
+    assert!(checked[0].usability.can_encrypt(), "{:?}", checked[0].usability);
+
+    let key = dearmor(EXPIRED_KEY);
+    let revocation = dearmor(EXPIRED_KEY_REVOCATION);
+    // The revocation belongs right after the primary key packet.
+    let user_id = packets::parse(&key).unwrap()[1].offset;
+    let revoked = [&key[..user_id], &revocation, &key[user_id..]].concat();
+    let mut forged = revocation.clone();
+    *forged.last_mut().unwrap() ^= 1;
+    let forged = [&key[..user_id], &forged, &key[user_id..]].concat();
+
+    let created = 0x5f5e1000;
+    let usability = audit::usability(&key, created + 3600).unwrap();
+    assert!(usability.can_encrypt());
+    assert_eq!(usability.encryption_keys.len(), 1);
+    let usability = audit::usability(&key, created + 2 * 86400).unwrap();
+    assert_eq!(usability.expired, Some(created + 86400));
+    assert!(usability.encryption_keys.is_empty());
+    assert_eq!(usability.expired_encryption_keys.len(), 1);
+    assert!(!usability.can_encrypt());
+    let usability = audit::usability(&revoked, created + 3600).unwrap();
+    assert!(usability.revoked);
+    assert!(!usability.can_encrypt());
+    let usability = audit::usability(&forged, created + 3600).unwrap();
+    assert!(!usability.revoked);
+    assert!(usability.can_encrypt());
+    let findings = audit::audit(&forged, created + 3600).unwrap();
+    assert!(findings.iter().any(|f| f.message.contains("do not verify")), "{:?}", findings);
+
+    // The override encrypts to the expired subkey.
+    let literal = LiteralOptions::default();
+    assert!(matches!(
+        crypto::encrypt_text(EXPIRED_KEY, "late", &literal, None, false).await,
+        Err(Error::ExpiredKey(Field::PublicKey))
+    ));
+    let encrypted = crypto::encrypt_text(EXPIRED_KEY, "late", &literal, None, true).await;
+    assert!(encrypted.is_ok(), "{:?}", encrypted.err());
+}
+
+#[tokio::test]
+async fn primary_keys_that_may_encrypt_are_used() {
+    let checked = crypto::check_recipient_keys(PRIMARY_ONLY_KEY).await.unwrap();
+    assert!(checked[0].usability.can_encrypt(), "{:?}", checked[0].usability);
+    let key_id = fingerprint::parse("7AA14CCA7DF046DF").unwrap();
+    assert_eq!(checked[0].usability.encryption_keys, vec![key_id]);
+    let encrypted = encrypt(PRIMARY_ONLY_KEY, "to the primary key").await;
+    let esk = &packets::parse(&dearmor(&encrypted)).unwrap()[0];
+    assert!(esk.details.contains(&"key ID 7AA14CCA7DF046DF".to_string()), "{:?}", esk.details);
+}
+
+#[tokio::test]
+async fn merged_keys_combine_signatures_once() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let twice = format!("{}\n{}", keys.public_key, keys.public_key);
+    let merged = crypto::merge_keys(&twice).await.unwrap();
+    assert_eq!(merged.report.sources, 2);
+    assert_eq!(merged.report.added_signatures, 0);
+    assert!(merged.report.duplicate_signatures > 0);
+    let tags = |armored: &str| -> Vec<u8> {
+        let data = &armor::decode_all(armored).unwrap()[0].data;
+        packets::parse(data).unwrap().iter().map(|p| p.tag).collect()
+    };
+    assert_eq!(tags(&merged.public_key), tags(&keys.public_key));
+
+    let encrypted = encrypt(&merged.public_key, "merged").await;
+    let decrypted = crypto::decrypt_text(&keys.secret_key, "", &encrypted)
+        .await
+        .unwrap();
+    assert_eq!(decrypted.text.as_str(), "merged");
+
+    // One copy carries the revocation, one a second user ID, one the user
+    // ID self-signature with an extra unhashed subpacket, and one a forged
+    // revocation and a user ID with a forged self-signature.
+    let key = dearmor(EXPIRED_KEY);
+    let parsed = packets::parse(&key).unwrap();
+    let (user_id, sig_start, subkey) = (parsed[1].offset, parsed[2].offset, parsed[3].offset);
+    let revocation = dearmor(EXPIRED_KEY_REVOCATION);
+    let revoked = [&key[..user_id], &revocation, &key[user_id..]].concat();
+
+    let sig = &key[subkey - parsed[2].length..subkey];
+    let hashed_end = 6 + u16::from_be_bytes([sig[4], sig[5]]) as usize;
+    let unhashed_len = u16::from_be_bytes([sig[hashed_end], sig[hashed_end + 1]]) as usize;
+    let unhashed_end = hashed_end + 2 + unhashed_len;
+    let resigned = [
+        &sig[..hashed_end],
+        &(unhashed_len as u16 + 3).to_be_bytes(),
+        &sig[hashed_end + 2..unhashed_end],
+        &[2, 101, 0],
+        &sig[unhashed_end..],
+    ]
+    .concat();
+    let resigned = [&key[..sig_start], &packet(2, &resigned), &key[subkey..]].concat();
+
+    let mut forged_revocation = revocation.clone();
+    *forged_revocation.last_mut().unwrap() ^= 1;
+    let mut forged_certification = sig.to_vec();
+    *forged_certification.last_mut().unwrap() ^= 1;
+    let forged = [
+        &key[..user_id],
+        &packet(2, &forged_revocation),
+        &packet(13, b"Mallory <mallory@example.org>"),
+        &packet(2, &forged_certification),
+    ]
+    .concat();
+
+    let copies = [revoked, dearmor(EXPIRED_KEY_TWO_USER_IDS), resigned, forged].concat();
+    let (data, report) = merge::merge(&copies).unwrap();
+    assert_eq!(report.sources, 4);
+    assert_eq!(report.added_user_ids, ["Gone <gone@work.example>"]);
+    assert_eq!(report.added_signatures, 1);
+    assert_eq!(report.duplicate_signatures, 4);
+    assert_eq!(report.dropped_user_ids, ["Mallory <mallory@example.org>"]);
+    assert_eq!(report.dropped_signatures.len(), 2, "{:?}", report.dropped_signatures);
+    assert_eq!(
+        report.dropped_signatures[0],
+        "signature 0x20 (key revocation) on the primary key"
+    );
+    assert!(report.to_text().contains("Dropped user ID \"Mallory <mallory@example.org>\""));
+    let parsed: Vec<u8> = packets::parse(&data).unwrap().iter().map(|p| p.tag).collect();
+    assert_eq!(parsed, [6, 2, 13, 2, 13, 2, 14, 2]);
+    assert!(audit::usability(&data, 0x5f5e1000 + 3600).unwrap().revoked);
+
+    let other = crypto::generate_keypair().await.unwrap();
+    let mixed = format!("{}\n{}", keys.public_key, other.public_key);
+    assert!(crypto::merge_keys(&mixed).await.is_err());
+}
+
+#[tokio::test]
+async fn recipients_are_checked_against_the_loaded_key() {
+    let alice = crypto::generate_keypair().await.unwrap();
+    let bob = crypto::generate_keypair().await.unwrap();
+    let encrypted = encrypt(&alice.public_key, "secret").await;
+
+    let unchecked = crypto::check_recipients(&encrypted, "").await.unwrap();
+    assert!(!unchecked.keys.is_empty());
+    assert!(unchecked.keys.iter().all(|r| r.matches.is_none()));
+
+    let for_bob = crypto::check_recipients(&encrypted, &bob.secret_key).await.unwrap();
+    assert!(!for_bob.any_match());
+    assert!(for_bob.keys.iter().all(|r| r.matches == Some(false)));
+
+    let for_alice = crypto::check_recipients(&encrypted, &alice.secret_key).await.unwrap();
+    assert!(for_alice.any_match());
+    assert!(!for_alice.passphrase);
+}
+
+#[tokio::test]
+async fn literal_metadata_roundtrip() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let options = LiteralOptions {
+        file_name: "notes.txt".to_string(),
+        date: packets::parse_time("2024-05-01 13:45"),
+        mode: DataMode::Utf8,
+    };
+    let encrypted = crypto::encrypt_text(&keys.public_key, "line 1\nline 2", &options, None, false)
+        .await
+        .unwrap();
+    let decrypted = crypto::decrypt_text(&keys.secret_key, "", &encrypted)
+        .await
+        .unwrap();
+    assert_eq!(decrypted.text.as_str(), "line 1\nline 2");
+    assert_eq!(decrypted.literal.file_name, "notes.txt");
+    assert_eq!(decrypted.literal.date, 1714571100);
+    assert_eq!(decrypted.literal.mode, DataMode::Utf8);
+
+    let hidden = LiteralOptions {
+        date: Some(0),
+        ..LiteralOptions::default()
+    };
+    let encrypted = crypto::encrypt_text_with_passphrase(
+        "correct horse",
+        "hi",
+        symmetric::S2kOptions::default(),
+        &hidden,
+        Compression::None,
+    )
+    .unwrap();
+    let decrypted = crypto::decrypt_text_with_passphrase("correct horse", &encrypted).unwrap();
+    assert_eq!(decrypted.literal, LiteralInfo::default());
+    let encrypted = encrypt(&keys.public_key, "now").await;
+    let decrypted = crypto::decrypt_text(&keys.secret_key, "", &encrypted)
+        .await
+        .unwrap();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    assert!(now.abs_diff(decrypted.literal.date) < 60, "{}", decrypted.literal.date);
+}
+
+#[test]
+fn sender_file_names_are_made_safe() {
+    let safe = |file_name: &str| {
+        LiteralInfo {
+            file_name: file_name.to_string(),
+            ..LiteralInfo::default()
+        }
+        .safe_file_name()
+    };
+    assert_eq!(safe("notes.txt").as_deref(), Some("notes.txt"));
+    assert_eq!(safe("../../.bashrc").as_deref(), Some("bashrc"));
+    assert_eq!(safe("C:\\Windows\\evil.exe").as_deref(), Some("CWindowsevil.exe"));
+    assert_eq!(safe("invoice\u{202e}fdp.exe\n").as_deref(), Some("invoicefdp.exe"));
+    assert_eq!(safe("/.."), None);
+    assert_eq!(safe(""), None);
+    let long = safe(&format!("{}.pdf", "x".repeat(300))).unwrap();
+    assert_eq!(long.chars().count(), 100);
+    assert!(long.ends_with("x.pdf"));
+}
+
+#[test]
+fn dates_are_checked_against_the_calendar() {
+    assert_eq!(packets::parse_time("2024-02-29"), Some(1709164800));
+    assert_eq!(packets::parse_time("2023-02-29"), None);
+    assert_eq!(packets::parse_time("2024-04-31 12:00"), None);
+    assert_eq!(packets::parse_time("2000-02-29 00:00 UTC"), Some(951782400));
+    assert_eq!(packets::parse_time("1900-02-29"), None);
+}
+
+#[tokio::test]
+async fn every_compression_roundtrips() {
+    let keys = crypto::generate_keypair().await.unwrap();
+    let literal = LiteralOptions::default();
+    for compression in Compression::ALL {
+        let encrypted = crypto::encrypt_text(
+            &keys.public_key,
+            "compress me",
+            &literal,
+            Some(compression),
+            false,
+        )
+        .await
+        .unwrap();
+        let decrypted = crypto::decrypt_text(&keys.secret_key, "", &encrypted)
+            .await
+            .unwrap();
+        assert_eq!(decrypted.text.as_str(), "compress me", "{}", compression);
+
+        let s2k = symmetric::S2kOptions::default();
+        let encrypted =
+            crypto::encrypt_text_with_passphrase("pw", "compress me", s2k, &literal, compression)
+                .unwrap();
+        let decrypted = crypto::decrypt_text_with_passphrase("pw", &encrypted).unwrap();
+        assert_eq!(decrypted.text.as_str(), "compress me", "{}", compression);
+    }
+}
+
+#[tokio::test]
+async fn encrypts_to_several_recipients_with_negotiated_algorithms() {
+    let alice = crypto::generate_keypair().await.unwrap();
+    let bob = crypto::generate_keypair().await.unwrap();
+    let both = format!("{}\n{}", alice.public_key, bob.public_key);
+
+    let negotiated = crypto::negotiate_algorithms(&both).await.unwrap();
+    assert!(negotiated.warnings.is_empty(), "{:?}", negotiated.warnings);
+    assert_ne!(negotiated.cipher, symmetric::SymmetricKeyAlgorithm::TripleDES);
+    // Generated v4 keys announce MDC but not SEIPD v2.
+    assert_eq!(negotiated.aead, None);
+    let encrypted = encrypt(&both, "for both").await;
+    let packets = crypto::list_packets(encrypted.as_bytes()).unwrap();
+    assert_eq!(packets.last().map(|p| (p.tag, p.version)), Some((18, Some(1))));
+
+    for keys in [&alice, &bob] {
+        let decrypted = crypto::decrypt_text(&keys.secret_key, "", &encrypted)
+            .await
+            .unwrap();
+        assert_eq!(decrypted.text.as_str(), "for both");
+    }
+}
+
+#[tokio::test]
+async fn encrypts_with_the_negotiated_aead_mode() {
+    let negotiated = crypto::negotiate_algorithms(SEIPD_V2_KEY).await.unwrap();
+    assert_eq!(negotiated.aead, Some(preferences::AeadMode::Ocb));
+    let encrypted = encrypt(SEIPD_V2_KEY, "sealed").await;
+    let packets = crypto::list_packets(encrypted.as_bytes()).unwrap();
+    assert_eq!((packets[0].tag, packets[0].version), (1, Some(6)));
+    assert_eq!((packets[1].tag, packets[1].version), (18, Some(2)));
+    assert!(packets[1].details.iter().any(|d| d.contains("OCB")), "{:?}", packets[1].details);
+
+    // A v6 key cannot share a message with a recipient limited to SEIPD v1.
+    let v4 = crypto::generate_keypair().await.unwrap();
+    let v6 = crypto::generate_keypair_with_profile(crypto::KeyProfile::Rfc9580)
+        .await
+        .unwrap();
+    let both = format!("{}\n{}", v4.public_key, v6.public_key);
+    let result = crypto::encrypt_text(&both, "x", &LiteralOptions::default(), None, false).await;
+    assert!(matches!(result, Err(Error::Encrypt(_))));
+}
+
+#[test]
+fn preferences_are_read_from_direct_key_signatures() {
+    let negotiated = preferences::negotiate(&[dearmor(DIRECT_KEY_PREFERENCES_KEY)]);
+    assert_eq!(negotiated.cipher, symmetric::SymmetricKeyAlgorithm::AES128);
+    assert_eq!(negotiated.aead, Some(preferences::AeadMode::Ocb));
+    assert!(negotiated.warnings.is_empty(), "{:?}", negotiated.warnings);
+}